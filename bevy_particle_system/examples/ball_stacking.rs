@@ -80,6 +80,7 @@ fn spawn_balls(
                 })
                 .insert(ParticleBundle {
                     collider: CircleCollider { radius },
+                    inertia: Inertia::circle(1., radius),
                     ..ParticleBundle::new_with_pos_and_vel(pos, vel)
                 });
         }
@@ -0,0 +1,107 @@
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_particle_system::*;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa { samples: 4 })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(XPBDPlugin::default())
+        .add_startup_system(spawn_camera)
+        .add_startup_system(spawn_boxes)
+        .run();
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::prelude::Projection::Orthographic(OrthographicProjection {
+            scale: 0.01,
+            ..Default::default()
+        }),
+        ..Camera3dBundle::default()
+    });
+}
+
+fn spawn_boxes(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let quad = meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE)));
+    let sphere = meshes.add(Mesh::from(shape::Icosphere {
+        radius: 1.,
+        subdivisions: 4,
+    }));
+
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+
+    let size = Vec2::new(20., 2.);
+    commands
+        .spawn(PbrBundle {
+            mesh: quad.clone(),
+            material: blue.clone(),
+            transform: Transform::from_scale(size.extend(1.)),
+            ..Default::default()
+        })
+        .insert(StaticBoxBundle {
+            pos: Pos(Vec2::new(0., -4.)),
+            collider: BoxCollider { size },
+            ..Default::default()
+        });
+
+    // A few stacks of boxes resting on the floor
+    let size = Vec2::splat(0.4);
+    for i in 0..6 {
+        for j in 0..3 {
+            let pos = Vec2::new(j as f32 * 1.5 - 1.5, -2.8 + i as f32 * size.y);
+            commands
+                .spawn(PbrBundle {
+                    mesh: quad.clone(),
+                    material: blue.clone(),
+                    transform: Transform {
+                        scale: size.extend(1.),
+                        translation: pos.extend(0.),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(DynamicBoxBundle {
+                    collider: BoxCollider { size },
+                    inertia: Inertia::rectangle(1., size),
+                    ..DynamicBoxBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+                });
+        }
+    }
+
+    // A ball thrown at the stacks
+    let radius = 0.2;
+    let pos = Vec2::new(-5., -2.);
+    let vel = Vec2::new(6., 2.);
+    commands
+        .spawn(PbrBundle {
+            mesh: sphere,
+            material: blue,
+            transform: Transform {
+                scale: Vec3::splat(radius),
+                translation: pos.extend(0.),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(ParticleBundle {
+            collider: CircleCollider { radius },
+            mass: Mass(3.),
+            inertia: Inertia::circle(3., radius),
+            ..ParticleBundle::new_with_pos_and_vel(pos, vel)
+        });
+}
@@ -0,0 +1,145 @@
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_particle_system::*;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa { samples: 4 })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(XPBDPlugin::default())
+        .add_startup_system(spawn_camera)
+        .add_startup_system(spawn_chute)
+        .add_system(spawn_marbles)
+        .add_system(despawn_marbles)
+        .run();
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::prelude::Projection::Orthographic(OrthographicProjection {
+            scale: 0.01,
+            ..Default::default()
+        }),
+        ..Camera3dBundle::default()
+    });
+}
+
+#[derive(Resource)]
+struct Materials {
+    blue: Handle<StandardMaterial>,
+    sphere: Handle<Mesh>,
+}
+
+fn spawn_chute(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+    let quad = meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE)));
+
+    // Zig-zag chute made of thin segments
+    let length = 5.;
+    for (pos, rot) in [
+        (Vec2::new(-1., 3.), -0.25),
+        (Vec2::new(1., 1.), 0.25),
+        (Vec2::new(-1., -1.), -0.25),
+    ] {
+        commands
+            .spawn(PbrBundle {
+                mesh: quad.clone(),
+                material: blue.clone(),
+                transform: Transform {
+                    scale: Vec3::new(length, 0.03, 1.),
+                    translation: pos.extend(0.),
+                    rotation: Quat::from_rotation_z(rot),
+                },
+                ..Default::default()
+            })
+            .insert(StaticSegmentBundle {
+                pos: Pos(pos),
+                rot: Rot(rot),
+                collider: SegmentCollider {
+                    a: Vec2::new(-length / 2., 0.),
+                    b: Vec2::new(length / 2., 0.),
+                },
+                ..Default::default()
+            });
+    }
+
+    // Row of pegs below the chute that scatter the marbles
+    let (length, radius) = (0.3, 0.08);
+    let capsule_mesh = meshes.add(Mesh::from(shape::Capsule {
+        radius,
+        depth: length,
+        ..Default::default()
+    }));
+    for i in 0..6 {
+        let pos = Vec2::new(i as f32 * 0.8 - 2., -3.);
+        commands
+            .spawn(PbrBundle {
+                mesh: capsule_mesh.clone(),
+                material: blue.clone(),
+                transform: Transform::from_translation(pos.extend(0.)),
+                ..Default::default()
+            })
+            .insert(StaticCapsuleBundle {
+                pos: Pos(pos),
+                collider: CapsuleCollider { length, radius },
+                ..Default::default()
+            });
+    }
+
+    commands.insert_resource(Materials {
+        blue,
+        sphere: meshes.add(Mesh::from(shape::Icosphere {
+            radius: 1.,
+            subdivisions: 4,
+        })),
+    });
+}
+
+fn spawn_marbles(mut commands: Commands, materials: Res<Materials>, time: Res<Time>, mut timer: Local<f32>) {
+    *timer -= time.delta_seconds();
+    if *timer > 0. {
+        return;
+    }
+    *timer = 0.2;
+
+    let radius = 0.1;
+    let pos = Vec2::new(-3., 4.5);
+    commands
+        .spawn(PbrBundle {
+            mesh: materials.sphere.clone(),
+            material: materials.blue.clone(),
+            transform: Transform {
+                scale: Vec3::splat(radius),
+                translation: pos.extend(0.),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(ParticleBundle {
+            collider: CircleCollider { radius },
+            inertia: Inertia::circle(1., radius),
+            ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+        });
+}
+
+fn despawn_marbles(mut commands: Commands, query: Query<(Entity, &Pos), With<Mass>>) {
+    for (entity, pos) in query.iter() {
+        if pos.0.y < -10. {
+            commands.entity(entity).despawn();
+        }
+    }
+}
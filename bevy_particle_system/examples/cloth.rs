@@ -0,0 +1,152 @@
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+    render::render_resource::PrimitiveTopology,
+};
+use bevy_particle_system::*;
+
+const SPACING: f32 = 0.2;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa { samples: 4 })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(XPBDPlugin::default())
+        .add_startup_system(spawn_camera)
+        .add_startup_system(spawn_scene)
+        .add_system(throw_balls)
+        .add_system(update_cloth_mesh)
+        .run();
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::prelude::Projection::Orthographic(OrthographicProjection {
+            scale: 0.01,
+            ..Default::default()
+        }),
+        ..Camera3dBundle::default()
+    });
+}
+
+#[derive(Resource)]
+struct Materials {
+    blue: Handle<StandardMaterial>,
+    sphere: Handle<Mesh>,
+}
+
+/// Line list drawn along every structural link that hasn't torn yet
+#[derive(Resource)]
+struct ClothMesh(Handle<Mesh>);
+
+fn spawn_scene(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+    let quad = meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE)));
+
+    for (pos, size) in [
+        (Vec2::new(-3., -2.), Vec2::new(1.5, 1.)),
+        (Vec2::new(0., -4.5), Vec2::new(12., 1.)),
+    ] {
+        commands
+            .spawn(PbrBundle {
+                mesh: quad.clone(),
+                material: blue.clone(),
+                transform: Transform::from_scale(size.extend(1.)),
+                ..Default::default()
+            })
+            .insert(StaticBoxBundle {
+                pos: Pos(pos),
+                collider: BoxCollider { size },
+                ..Default::default()
+            });
+    }
+
+    // A sheet that drapes over the box on the left
+    ClothBuilder::new(Vec2::new(-5., 0.), 21, 3, SPACING).spawn(&mut commands);
+
+    // A curtain hanging from its top corners that tears when the balls hit it
+    let columns = 16;
+    ClothBuilder::new(Vec2::new(1., 3.), columns, 12, SPACING)
+        .pin(0, 0)
+        .pin(columns - 1, 0)
+        .tear_force(100.)
+        .spawn(&mut commands);
+
+    let mesh = meshes.add(Mesh::new(PrimitiveTopology::LineList));
+    commands.spawn(PbrBundle {
+        mesh: mesh.clone(),
+        material: blue.clone(),
+        ..Default::default()
+    });
+    commands.insert_resource(ClothMesh(mesh));
+
+    commands.insert_resource(Materials {
+        blue,
+        sphere: meshes.add(Mesh::from(shape::Icosphere {
+            radius: 1.,
+            subdivisions: 4,
+        })),
+    });
+}
+
+fn update_cloth_mesh(
+    links: Query<&DistanceConstraint>,
+    positions: Query<&Pos>,
+    cloth_mesh: Res<ClothMesh>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Some(mesh) = meshes.get_mut(&cloth_mesh.0) else {
+        return;
+    };
+    let mut vertices: Vec<[f32; 3]> = vec![];
+    for link in links.iter().filter(|link| link.rest_length <= SPACING * 1.01) {
+        let (Ok(a), Ok(b)) = (positions.get(link.a), positions.get(link.b)) else {
+            continue;
+        };
+        vertices.push([a.0.x, a.0.y, 0.]);
+        vertices.push([b.0.x, b.0.y, 0.]);
+    }
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 0., 1.]; vertices.len()]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; vertices.len()]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+}
+
+fn throw_balls(mut commands: Commands, materials: Res<Materials>, time: Res<Time>, mut timer: Local<f32>) {
+    *timer += time.delta_seconds();
+    if *timer < 3. {
+        return;
+    }
+    *timer = 0.;
+
+    let radius = 0.3;
+    let pos = Vec2::new(6., 2.);
+    commands
+        .spawn(PbrBundle {
+            mesh: materials.sphere.clone(),
+            material: materials.blue.clone(),
+            transform: Transform {
+                scale: Vec3::splat(radius),
+                translation: pos.extend(0.),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(ParticleBundle {
+            collider: CircleCollider { radius },
+            mass: Mass(5.),
+            inertia: Inertia::circle(5., radius),
+            ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::new(-8., 3.))
+        });
+}
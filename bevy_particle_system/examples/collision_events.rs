@@ -0,0 +1,167 @@
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_particle_system::*;
+use rand::random;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa { samples: 4 })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(XPBDPlugin::default())
+        // Balls settling after a small hop keep their collision instead of flashing again
+        .insert_resource(CollisionEndDelay(0.2))
+        .add_startup_system(startup)
+        .add_system(spawn_balls)
+        .add_system(flash_on_impact)
+        .add_system(fade_flashes)
+        .add_system(despawn_balls)
+        .run();
+}
+
+#[derive(Resource)]
+struct Materials {
+    blue: Handle<StandardMaterial>,
+    flash: Handle<StandardMaterial>,
+    sphere: Handle<Mesh>,
+}
+
+/// Time left before a flashing ball turns blue again
+#[derive(Component)]
+struct Flash(f32);
+
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::prelude::Projection::Orthographic(OrthographicProjection {
+            scale: 0.01,
+            ..Default::default()
+        }),
+        ..Camera3dBundle::default()
+    });
+
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+    let quad = meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE)));
+
+    // Steps for the balls to bounce down
+    let steps = [
+        (Vec2::new(-3., 1.), Vec2::new(3., 0.3), -0.2),
+        (Vec2::new(1., -1.), Vec2::new(3., 0.3), 0.2),
+        (Vec2::new(-1., -3.2), Vec2::new(8., 0.3), 0.),
+    ];
+    for (pos, size, angle) in steps {
+        commands
+            .spawn(PbrBundle {
+                mesh: quad.clone(),
+                material: blue.clone(),
+                transform: Transform {
+                    scale: size.extend(1.),
+                    translation: pos.extend(0.),
+                    rotation: Quat::from_rotation_z(angle),
+                },
+                ..Default::default()
+            })
+            .insert(StaticBoxBundle {
+                pos: Pos(pos),
+                rot: Rot(angle),
+                collider: BoxCollider { size },
+                ..Default::default()
+            });
+    }
+
+    commands.insert_resource(Materials {
+        blue,
+        flash: materials.add(StandardMaterial {
+            base_color: Color::rgb(1., 0.5, 0.2),
+            unlit: true,
+            ..Default::default()
+        }),
+        sphere: meshes.add(Mesh::from(shape::Icosphere {
+            radius: 1.,
+            subdivisions: 4,
+        })),
+    });
+}
+
+fn spawn_balls(mut commands: Commands, materials: Res<Materials>, time: Res<Time>, mut timer: Local<f32>) {
+    *timer -= time.delta_seconds();
+    if *timer > 0. {
+        return;
+    }
+    *timer = 0.5;
+
+    let radius = 0.2;
+    let pos = Vec2::new(-3.5 + random::<f32>(), 4.);
+    commands
+        .spawn(PbrBundle {
+            mesh: materials.sphere.clone(),
+            material: materials.blue.clone(),
+            transform: Transform {
+                scale: Vec3::splat(radius),
+                translation: pos.extend(0.),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(ParticleBundle {
+            collider: CircleCollider { radius },
+            inertia: Inertia::circle(1., radius),
+            restitution: Restitution(0.7),
+            ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+        });
+}
+
+/// Lights up every ball that starts touching something hard enough, and leaves
+/// balls that just roll along alone
+fn flash_on_impact(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionStarted>,
+    materials: Res<Materials>,
+    balls: Query<(), With<Mass>>,
+) {
+    for CollisionStarted(collision) in collisions.iter() {
+        if collision.impulse < 1. {
+            continue;
+        }
+        for entity in [collision.entity_a, collision.entity_b] {
+            if balls.contains(entity) {
+                info!("impact of {:.2} N s", collision.impulse);
+                commands.entity(entity).insert((materials.flash.clone(), Flash(0.2)));
+            }
+        }
+    }
+}
+
+fn fade_flashes(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Flash)>,
+    materials: Res<Materials>,
+    time: Res<Time>,
+) {
+    for (entity, mut flash) in query.iter_mut() {
+        flash.0 -= time.delta_seconds();
+        if flash.0 <= 0. {
+            commands.entity(entity).remove::<Flash>().insert(materials.blue.clone());
+        }
+    }
+}
+
+fn despawn_balls(mut commands: Commands, query: Query<(Entity, &Pos)>) {
+    for (entity, pos) in query.iter() {
+        if pos.0.y < -20. {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -35,11 +35,11 @@ fn startup(
             material: white.clone(),
             ..Default::default()
         })
-        .insert(ParticleBundle::new_with_pos_and_vel(
-            Vec2::new(-2., 0.),
-            Vec2::new(2., 0.),
-        ))
-        .insert(Mass(3.));
+        .insert(ParticleBundle {
+            mass: Mass(3.),
+            inertia: Inertia::circle(3., CircleCollider::default().radius),
+            ..ParticleBundle::new_with_pos_and_vel(Vec2::new(-2., 0.), Vec2::new(2., 0.))
+        });
 
     // Right particle
     commands
@@ -48,11 +48,11 @@ fn startup(
             material: white.clone(),
             ..Default::default()
         })
-        .insert(ParticleBundle::new_with_pos_and_vel(
-            Vec2::new(2., 0.),
-            Vec2::new(-2., 0.),
-        ))
-        .insert(Mass(1.));
+        .insert(ParticleBundle {
+            mass: Mass(1.),
+            inertia: Inertia::circle(1., CircleCollider::default().radius),
+            ..ParticleBundle::new_with_pos_and_vel(Vec2::new(2., 0.), Vec2::new(-2., 0.))
+        });
 
     commands.spawn(Camera3dBundle {
         transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
@@ -0,0 +1,122 @@
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_particle_system::*;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa { samples: 4 })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(XPBDPlugin::default())
+        .add_startup_system(spawn_camera)
+        .add_startup_system(spawn_links)
+        .run();
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::prelude::Projection::Orthographic(OrthographicProjection {
+            scale: 0.01,
+            ..Default::default()
+        }),
+        ..Camera3dBundle::default()
+    });
+}
+
+fn spawn_links(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let sphere = meshes.add(Mesh::from(shape::Icosphere {
+        radius: 1.,
+        subdivisions: 4,
+    }));
+
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+
+    let size = Vec2::new(20., 2.);
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE))),
+            material: blue.clone(),
+            transform: Transform::from_scale(size.extend(1.)),
+            ..Default::default()
+        })
+        .insert(StaticBoxBundle {
+            pos: Pos(Vec2::new(0., -4.)),
+            collider: BoxCollider { size },
+            ..Default::default()
+        });
+
+    let spawn_particle = |commands: &mut Commands, pos: Vec2, radius: f32| {
+        commands
+            .spawn(PbrBundle {
+                mesh: sphere.clone(),
+                material: blue.clone(),
+                transform: Transform {
+                    scale: Vec3::splat(radius),
+                    translation: pos.extend(0.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(ParticleBundle {
+                collider: CircleCollider { radius },
+                inertia: Inertia::circle(1., radius),
+                ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+            })
+            .id()
+    };
+
+    // A rigid chain swinging from a static anchor
+    let (start, end) = (Vec2::new(-2., 3.), Vec2::new(0.5, 3.));
+    let chain = RopeBuilder::new(start, end)
+        .points(11)
+        .radius(0.1)
+        .pin_start()
+        .spawn(&mut commands);
+    for (i, link) in chain.points.iter().enumerate().skip(1) {
+        commands.entity(*link).insert(PbrBundle {
+            mesh: sphere.clone(),
+            material: blue.clone(),
+            transform: Transform {
+                scale: Vec3::splat(0.1),
+                translation: start.lerp(end, i as f32 / 10.).extend(0.),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+    }
+
+    // A springy square with diagonal braces that bounces on the floor
+    let corners = [
+        Vec2::new(2., 1.),
+        Vec2::new(3., 1.),
+        Vec2::new(3., 2.),
+        Vec2::new(2., 2.),
+    ];
+    let particles: Vec<Entity> = corners
+        .iter()
+        .map(|pos| spawn_particle(&mut commands, *pos, 0.15))
+        .collect();
+    for i in 0..corners.len() {
+        for j in i + 1..corners.len() {
+            commands.spawn(DistanceConstraint {
+                a: particles[i],
+                b: particles[j],
+                rest_length: corners[i].distance(corners[j]),
+                compliance: 0.001,
+            });
+        }
+    }
+}
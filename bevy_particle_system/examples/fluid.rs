@@ -0,0 +1,150 @@
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_particle_system::*;
+use rand::random;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa { samples: 4 })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(XPBDPlugin::default())
+        .add_startup_system(spawn_camera)
+        .add_startup_system(spawn_tank)
+        .add_system(pour_fluid)
+        .run();
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::prelude::Projection::Orthographic(OrthographicProjection {
+            scale: 0.01,
+            ..Default::default()
+        }),
+        ..Camera3dBundle::default()
+    });
+}
+
+#[derive(Resource)]
+struct Materials {
+    water: Handle<StandardMaterial>,
+    sphere: Handle<Mesh>,
+}
+
+const PARTICLE_RADIUS: f32 = 0.1;
+
+fn spawn_tank(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+    let water = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.2, 0.4, 0.9),
+        unlit: true,
+        ..Default::default()
+    });
+    let sphere = meshes.add(Mesh::from(shape::Icosphere {
+        radius: 1.,
+        subdivisions: 2,
+    }));
+
+    // Floor, walls and a tilted step in the middle for the wave to break over
+    let walls = [
+        (Vec2::new(0., -3.5), Vec2::new(9., 1.), 0.),
+        (Vec2::new(-4.5, 0.), Vec2::new(1., 8.), 0.),
+        (Vec2::new(4.5, 0.), Vec2::new(1., 8.), 0.),
+        (Vec2::new(1.5, -2.8), Vec2::new(1.5, 0.6), 0.3),
+    ];
+    for (pos, size, angle) in walls {
+        commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE))),
+                material: blue.clone(),
+                transform: Transform::from_scale(size.extend(1.)),
+                ..Default::default()
+            })
+            .insert(StaticBoxBundle {
+                pos: Pos(pos),
+                rot: Rot(angle),
+                collider: BoxCollider { size },
+                ..Default::default()
+            });
+    }
+
+    // A column of water held against the left wall, released straight away
+    let spacing = 2. * PARTICLE_RADIUS;
+    for x in 0..12 {
+        for y in 0..24 {
+            // Small offsets so the grid doesn't stand up in perfectly stacked columns
+            let jitter = Vec2::new(random::<f32>() - 0.5, 0.) * 0.05;
+            let pos = Vec2::new(-3.9, -2.9) + Vec2::new(x as f32, y as f32) * spacing + jitter;
+            spawn_water(&mut commands, &water, &sphere, pos, Vec2::ZERO);
+        }
+    }
+
+    commands.insert_resource(Materials { water, sphere });
+}
+
+fn spawn_water(
+    commands: &mut Commands,
+    water: &Handle<StandardMaterial>,
+    sphere: &Handle<Mesh>,
+    pos: Vec2,
+    vel: Vec2,
+) {
+    commands.spawn((
+        PbrBundle {
+            mesh: sphere.clone(),
+            material: water.clone(),
+            transform: Transform {
+                scale: Vec3::splat(PARTICLE_RADIUS),
+                translation: pos.extend(0.),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        ParticleBundle {
+            collider: CircleCollider {
+                radius: PARTICLE_RADIUS,
+            },
+            inertia: Inertia::circle(1., PARTICLE_RADIUS),
+            restitution: Restitution(0.),
+            friction: Friction {
+                static_coeff: 0.,
+                dynamic_coeff: 0.,
+            },
+            ..ParticleBundle::new_with_pos_and_vel(pos, vel)
+        },
+        Fluid,
+    ));
+}
+
+/// Holding the left mouse button pours a stream of water from the top right
+fn pour_fluid(
+    mut commands: Commands,
+    materials: Res<Materials>,
+    buttons: Res<Input<MouseButton>>,
+    time: Res<Time>,
+    mut timer: Local<f32>,
+) {
+    *timer -= time.delta_seconds();
+    if !buttons.pressed(MouseButton::Left) || *timer > 0. {
+        return;
+    }
+    *timer = 0.05;
+
+    for i in 0..3 {
+        let pos = Vec2::new(3.5, 3. + i as f32 * 2. * PARTICLE_RADIUS);
+        spawn_water(&mut commands, &materials.water, &materials.sphere, pos, Vec2::new(-3., 0.));
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_particle_system::*;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa { samples: 4 })
+        .insert_resource(TransformSync::Interpolated)
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        // A deliberately slow timestep so the difference is easy to see
+        .add_plugin(XPBDPlugin {
+            timestep: 1. / 15.,
+            ..Default::default()
+        })
+        .add_startup_system(startup)
+        .add_system(toggle_interpolation)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::prelude::Projection::Orthographic(OrthographicProjection {
+            scale: 0.01,
+            ..Default::default()
+        }),
+        ..Camera3dBundle::default()
+    });
+
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+    let quad = meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE)));
+    let sphere = meshes.add(Mesh::from(shape::Icosphere {
+        radius: 1.,
+        subdivisions: 4,
+    }));
+
+    let walls = [
+        (Vec2::new(0., -3.5), Vec2::new(10., 0.5)),
+        (Vec2::new(-5., 0.), Vec2::new(0.5, 7.5)),
+        (Vec2::new(5., 0.), Vec2::new(0.5, 7.5)),
+    ];
+    for (pos, size) in walls {
+        commands
+            .spawn(PbrBundle {
+                mesh: quad.clone(),
+                material: blue.clone(),
+                transform: Transform::from_scale(size.extend(1.)),
+                ..Default::default()
+            })
+            .insert(StaticBoxBundle {
+                pos: Pos(pos),
+                collider: BoxCollider { size },
+                ..Default::default()
+            });
+    }
+
+    // Bouncy balls thrown sideways so they keep crossing the box
+    let radius = 0.3;
+    for i in 0..5 {
+        let pos = Vec2::new(-3. + i as f32 * 1.5, 2.);
+        let vel = Vec2::new(if i % 2 == 0 { 4. } else { -4. }, 0.);
+        commands
+            .spawn(PbrBundle {
+                mesh: sphere.clone(),
+                material: blue.clone(),
+                transform: Transform {
+                    scale: Vec3::splat(radius),
+                    translation: pos.extend(0.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(ParticleBundle {
+                collider: CircleCollider { radius },
+                inertia: Inertia::circle(1., radius),
+                restitution: Restitution(0.9),
+                ..ParticleBundle::new_with_pos_and_vel(pos, vel)
+            });
+    }
+}
+
+/// Space switches between interpolated and immediate transforms
+fn toggle_interpolation(keys: Res<Input<KeyCode>>, mut sync: ResMut<TransformSync>) {
+    if keys.just_pressed(KeyCode::Space) {
+        *sync = match *sync {
+            TransformSync::Immediate => TransformSync::Interpolated,
+            TransformSync::Interpolated => TransformSync::Immediate,
+        };
+        info!("{:?}", *sync);
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_particle_system::*;
+use rand::random;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa { samples: 4 })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(XPBDPlugin::default())
+        .add_startup_system(startup)
+        .add_system(move_platform)
+        .add_system(spawn_marbles)
+        .add_system(despawn_marbles)
+        .run();
+}
+
+#[derive(Resource)]
+struct Materials {
+    blue: Handle<StandardMaterial>,
+    sphere: Handle<Mesh>,
+}
+
+/// Platform that slides back and forth between `min_x` and `max_x`
+#[derive(Component)]
+struct Shuttle {
+    min_x: f32,
+    max_x: f32,
+    speed: f32,
+}
+
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::prelude::Projection::Orthographic(OrthographicProjection {
+            scale: 0.01,
+            ..Default::default()
+        }),
+        ..Camera3dBundle::default()
+    });
+
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+    let quad = meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE)));
+
+    // Box the paddles stir around in
+    let walls = [
+        (Vec2::new(-2., -3.5), Vec2::new(5., 0.5)),
+        (Vec2::new(-4.25, -1.), Vec2::new(0.5, 5.5)),
+        (Vec2::new(0.25, -2.), Vec2::new(0.5, 3.5)),
+    ];
+    for (pos, size) in walls {
+        commands
+            .spawn(PbrBundle {
+                mesh: quad.clone(),
+                material: blue.clone(),
+                transform: Transform::from_scale(size.extend(1.)),
+                ..Default::default()
+            })
+            .insert(StaticBoxBundle {
+                pos: Pos(pos),
+                collider: BoxCollider { size },
+                ..Default::default()
+            });
+    }
+
+    // A cross of two paddles spinning together
+    for angle in [0., std::f32::consts::FRAC_PI_2] {
+        let size = Vec2::new(2.5, 0.15);
+        commands.spawn((
+            PbrBundle {
+                mesh: quad.clone(),
+                material: blue.clone(),
+                transform: Transform::from_scale(size.extend(1.)),
+                ..Default::default()
+            },
+            StaticBoxBundle {
+                pos: Pos(Vec2::new(-2., -1.8)),
+                rot: Rot(angle),
+                collider: BoxCollider { size },
+                ..Default::default()
+            },
+            KinematicBundle::new(Vec2::ZERO, 1.5),
+        ));
+    }
+
+    // A shuttle that carries whatever lands on it from side to side
+    let size = Vec2::new(1.5, 0.2);
+    commands.spawn((
+        PbrBundle {
+            mesh: quad,
+            material: blue.clone(),
+            transform: Transform::from_scale(size.extend(1.)),
+            ..Default::default()
+        },
+        StaticBoxBundle {
+            pos: Pos(Vec2::new(2., -1.)),
+            collider: BoxCollider { size },
+            ..Default::default()
+        },
+        KinematicBundle::new(Vec2::new(1., 0.), 0.),
+        Shuttle {
+            min_x: 1.5,
+            max_x: 5.,
+            speed: 1.,
+        },
+    ));
+
+    commands.insert_resource(Materials {
+        blue,
+        sphere: meshes.add(Mesh::from(shape::Icosphere {
+            radius: 1.,
+            subdivisions: 4,
+        })),
+    });
+}
+
+/// Kinematic bodies are steered by setting their velocity
+fn move_platform(mut query: Query<(&Pos, &mut Vel, &Shuttle)>) {
+    for (pos, mut vel, shuttle) in query.iter_mut() {
+        if pos.0.x >= shuttle.max_x {
+            vel.0.x = -shuttle.speed;
+        } else if pos.0.x <= shuttle.min_x {
+            vel.0.x = shuttle.speed;
+        }
+    }
+}
+
+fn spawn_marbles(
+    mut commands: Commands,
+    materials: Res<Materials>,
+    time: Res<Time>,
+    mut timer: Local<f32>,
+    marbles: Query<Entity, With<Mass>>,
+) {
+    *timer -= time.delta_seconds();
+    if *timer > 0. || marbles.iter().len() >= 150 {
+        return;
+    }
+    *timer = 0.1;
+
+    let radius = 0.1;
+    // Drop them either into the paddle box or onto the shuttle
+    let x = if random::<bool>() { -2. } else { 3. };
+    let pos = Vec2::new(x + random::<f32>() - 0.5, 3.);
+    commands
+        .spawn(PbrBundle {
+            mesh: materials.sphere.clone(),
+            material: materials.blue.clone(),
+            transform: Transform {
+                scale: Vec3::splat(radius),
+                translation: pos.extend(0.),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(ParticleBundle {
+            collider: CircleCollider { radius },
+            inertia: Inertia::circle(1., radius),
+            ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+        });
+}
+
+fn despawn_marbles(mut commands: Commands, query: Query<(Entity, &Pos), With<Mass>>) {
+    for (entity, pos) in query.iter() {
+        if pos.0.y < -20. {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use bevy::{time::FixedTimestep, prelude::*, input::mouse::{MouseMotion}};
+use bevy::{time::FixedTimestep, prelude::*, input::mouse::{MouseMotion}, render::{mesh::Indices, render_resource::PrimitiveTopology}};
 use bevy_particle_system::*;
 use rand::random;
 
@@ -7,7 +7,10 @@ fn main() {
         .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
         .insert_resource(Msaa { samples: 4 })
         .add_plugins(DefaultPlugins)
-        .add_plugin(XPBDPlugin::default())
+        .add_plugin(XPBDPlugin {
+            deterministic: true,
+            ..Default::default()
+        })
         .add_plugin(bevy_editor_pls::EditorPlugin)
         .add_startup_system(startup)
         .add_system_set(
@@ -17,6 +20,8 @@ fn main() {
         )
         .add_system(despawn_marbles)
         .add_system(despawn_marbles_at_height)
+        .add_system(save_and_load)
+        .add_system(toggle_recording)
         .run();
 }
 
@@ -84,10 +89,45 @@ fn startup(
             ..Default::default()
         });
 
+    // Hopper that funnels the marbles into a narrow stream
+    for side in [-1., 1.] {
+        let collider = ConvexPolygonCollider::new(vec![
+            Vec2::new(side * 2.5, 2.),
+            Vec2::new(side * 2.3, 2.),
+            Vec2::new(side * 0.25, 0.5),
+            Vec2::new(side * 0.45, 0.5),
+        ]);
+        commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(polygon_mesh(&collider)),
+                material: blue.clone(),
+                ..Default::default()
+            })
+            .insert(StaticPolygonBundle {
+                collider,
+                ..Default::default()
+            });
+    }
+
     commands.insert_resource(Meshes { sphere });
     commands.insert_resource(Materials { blue });
 }
 
+/// Flat mesh of a polygon as a triangle fan around its first vertex
+fn polygon_mesh(polygon: &ConvexPolygonCollider) -> Mesh {
+    let positions: Vec<[f32; 3]> = polygon.vertices.iter().map(|v| [v.x, v.y, 0.]).collect();
+    let indices = (1..polygon.vertices.len() as u32 - 1)
+        .flat_map(|i| [0, i, i + 1])
+        .collect();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 0., 1.]; positions.len()]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; positions.len()]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
 fn spawn_marbles(
     mut commands: Commands,
     materials: Res<Materials>, 
@@ -117,6 +157,7 @@ fn spawn_marbles(
             })
             .insert(ParticleBundle {
                 collider: CircleCollider { radius },
+                inertia: Inertia::circle(1., radius),
                 ..ParticleBundle::new_with_pos_and_vel(pos, vel)
             }); 
     }
@@ -138,3 +179,68 @@ fn despawn_marbles(mut commands: Commands, query: Query<(Entity, &Pos)>, buttons
         }
     }   
 }
+
+/// F5 saves the physics state to `marble_pour.ron`, F9 replaces the marbles with the saved ones
+fn save_and_load(world: &mut World) {
+    let keys = world.resource::<Input<KeyCode>>();
+    let (save, load) = (keys.just_pressed(KeyCode::F5), keys.just_pressed(KeyCode::F9));
+    let path = "marble_pour.ron";
+
+    if save {
+        match Snapshot::capture(world).save(path) {
+            Ok(()) => info!("saved {path}"),
+            Err(err) => error!("couldn't save {path}: {err}"),
+        }
+    }
+
+    if load {
+        let mut snapshot = match Snapshot::load(path) {
+            Ok(snapshot) => snapshot,
+            Err(err) => return error!("couldn't load {path}: {err}"),
+        };
+        // The floor and the hopper are already there
+        snapshot.retain_bodies(|body| body.mass.is_some());
+
+        let marbles: Vec<Entity> = world.query_filtered::<Entity, With<Mass>>().iter(world).collect();
+        for marble in marbles {
+            world.despawn(marble);
+        }
+
+        let sphere = world.resource::<Meshes>().sphere.clone();
+        let blue = world.resource::<Materials>().blue.clone();
+        for (entity, body) in snapshot.restore(world).into_iter().zip(&snapshot.bodies) {
+            let radius = body.circle.as_ref().map_or(0.1, |circle| circle.radius);
+            world.entity_mut(entity).insert(PbrBundle {
+                mesh: sphere.clone(),
+                material: blue.clone(),
+                transform: Transform {
+                    scale: Vec3::splat(radius),
+                    translation: body.pos.0.extend(0.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        }
+        info!("loaded {path}");
+    }
+}
+
+/// R starts recording the session and stops it again, writing `marble_pour_recording.ron`.
+/// `cargo run --example playback marble_pour_recording.ron` plays it back without a window.
+fn toggle_recording(world: &mut World) {
+    if !world.resource::<Input<KeyCode>>().just_pressed(KeyCode::R) {
+        return;
+    }
+    let path = "marble_pour_recording.ron";
+
+    match PhysicsRecorder::stop(world) {
+        Some(recording) => match recording.save(path) {
+            Ok(()) => info!("saved {} steps to {path}", recording.steps),
+            Err(err) => error!("couldn't save {path}: {err}"),
+        },
+        None => {
+            PhysicsRecorder::start(world);
+            info!("recording");
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use bevy_particle_system::*;
+
+// Plays a recording back headlessly, e.g. one made in marble_pour with
+// `cargo run --example playback marble_pour_recording.ron`, and prints the
+// state hash it ends on.
+fn main() {
+    let path = std::env::args().nth(1).expect("usage: playback <recording.ron>");
+    let recording = Recording::load(&path).unwrap_or_else(|err| panic!("couldn't load {path}: {err}"));
+    let mut playback = Playback::new(recording);
+    playback.run();
+    let state = playback.world.resource::<StateHash>();
+    println!("played {} steps, final hash {:016x}", state.step, state.hash);
+}
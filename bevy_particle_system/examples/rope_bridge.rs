@@ -0,0 +1,134 @@
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+    render::render_resource::PrimitiveTopology,
+};
+use bevy_particle_system::*;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa { samples: 4 })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(XPBDPlugin::default())
+        .add_startup_system(spawn_camera)
+        .add_startup_system(spawn_bridge)
+        .add_system(spawn_marbles)
+        .add_system(update_rope_meshes)
+        .run();
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::prelude::Projection::Orthographic(OrthographicProjection {
+            scale: 0.01,
+            ..Default::default()
+        }),
+        ..Camera3dBundle::default()
+    });
+}
+
+#[derive(Resource)]
+struct Materials {
+    blue: Handle<StandardMaterial>,
+    sphere: Handle<Mesh>,
+}
+
+/// Line strip drawn along a rope
+#[derive(Component)]
+struct RopeMesh {
+    rope: Rope,
+    mesh: Handle<Mesh>,
+}
+
+fn spawn_bridge(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+
+    // A sagging bridge pinned at both ends and a rope hanging from one end
+    let ropes = [
+        RopeBuilder::new(Vec2::new(-3., 0.), Vec2::new(3., 0.))
+            .points(31)
+            .length(6.5)
+            .pin_start()
+            .pin_end(),
+        RopeBuilder::new(Vec2::new(3.5, 3.), Vec2::new(5.5, 3.))
+            .points(11)
+            .compliance(0.0001)
+            .pin_start(),
+    ];
+    for builder in ropes {
+        let rope = builder.spawn(&mut commands);
+        let mesh = meshes.add(Mesh::new(PrimitiveTopology::LineStrip));
+        commands.spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: blue.clone(),
+                ..Default::default()
+            },
+            RopeMesh { rope, mesh },
+        ));
+    }
+
+    commands.insert_resource(Materials {
+        blue,
+        sphere: meshes.add(Mesh::from(shape::Icosphere {
+            radius: 1.,
+            subdivisions: 4,
+        })),
+    });
+}
+
+fn update_rope_meshes(query: Query<&RopeMesh>, positions: Query<&Pos>, mut meshes: ResMut<Assets<Mesh>>) {
+    for rope_mesh in query.iter() {
+        let Some(mesh) = meshes.get_mut(&rope_mesh.mesh) else {
+            continue;
+        };
+        let vertices: Vec<[f32; 3]> = rope_mesh
+            .rope
+            .polyline(&positions)
+            .iter()
+            .map(|pos| [pos.x, pos.y, 0.])
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 0., 1.]; vertices.len()]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; vertices.len()]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+    }
+}
+
+fn spawn_marbles(mut commands: Commands, materials: Res<Materials>, time: Res<Time>, mut timer: Local<f32>) {
+    *timer -= time.delta_seconds();
+    if *timer > 0. {
+        return;
+    }
+    *timer = 1.;
+
+    let radius = 0.2;
+    let pos = Vec2::new(rand::random::<f32>() * 4. - 2., 4.);
+    commands
+        .spawn(PbrBundle {
+            mesh: materials.sphere.clone(),
+            material: materials.blue.clone(),
+            transform: Transform {
+                scale: Vec3::splat(radius),
+                translation: pos.extend(0.),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(ParticleBundle {
+            collider: CircleCollider { radius },
+            inertia: Inertia::circle(1., radius),
+            ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+        });
+}
@@ -0,0 +1,159 @@
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    time::FixedTimestep,
+};
+use bevy_particle_system::*;
+use rand::random;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa { samples: 4 })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(XPBDPlugin::default())
+        .add_startup_system(startup)
+        .add_system_set(
+            SystemSet::new()
+                .with_run_criteria(FixedTimestep::step(1. / 10.))
+                .with_system(pour_sand),
+        )
+        .add_system(despawn_fallen)
+        .run();
+}
+
+#[derive(Resource)]
+struct Materials {
+    sand: Handle<StandardMaterial>,
+    marble: Handle<StandardMaterial>,
+    sphere: Handle<Mesh>,
+}
+
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::prelude::Projection::Orthographic(OrthographicProjection {
+            scale: 0.01,
+            ..Default::default()
+        }),
+        ..Camera3dBundle::default()
+    });
+
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+
+    let size = Vec2::new(12., 1.);
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE))),
+            material: blue.clone(),
+            transform: Transform::from_scale(size.extend(1.)),
+            ..Default::default()
+        })
+        .insert(StaticBoxBundle {
+            pos: Pos(Vec2::new(0., -3.5)),
+            collider: BoxCollider { size },
+            ..Default::default()
+        });
+
+    // Two hoppers side by side, sand goes through the left one and marbles through the right
+    for center in [-3., 3.] {
+        for side in [-1., 1.] {
+            let collider = ConvexPolygonCollider::new(vec![
+                Vec2::new(center + side * 1.5, 2.5),
+                Vec2::new(center + side * 1.3, 2.5),
+                Vec2::new(center + side * 0.2, 1.),
+                Vec2::new(center + side * 0.4, 1.),
+            ]);
+            commands
+                .spawn(PbrBundle {
+                    mesh: meshes.add(polygon_mesh(&collider)),
+                    material: blue.clone(),
+                    ..Default::default()
+                })
+                .insert(StaticPolygonBundle {
+                    collider,
+                    ..Default::default()
+                });
+        }
+    }
+
+    commands.insert_resource(Materials {
+        sand: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.8, 0.7, 0.4),
+            unlit: true,
+            ..Default::default()
+        }),
+        marble: blue,
+        sphere: meshes.add(Mesh::from(shape::Icosphere {
+            radius: 1.,
+            subdivisions: 2,
+        })),
+    });
+}
+
+/// Flat mesh of a polygon as a triangle fan around its first vertex
+fn polygon_mesh(polygon: &ConvexPolygonCollider) -> Mesh {
+    let positions: Vec<[f32; 3]> = polygon.vertices.iter().map(|v| [v.x, v.y, 0.]).collect();
+    let indices = (1..polygon.vertices.len() as u32 - 1)
+        .flat_map(|i| [0, i, i + 1])
+        .collect();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 0., 1.]; positions.len()]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; positions.len()]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+/// Drops the same grains into both hoppers, only the sand is granular
+fn pour_sand(mut commands: Commands, materials: Res<Materials>, query: Query<&Pos>) {
+    let radius = 0.1;
+    for (center, granular) in [(-3., true), (3., false)] {
+        let pos = Vec2::new(center + (random::<f32>() - 0.5) * 2., 3. + random::<f32>() * 0.5);
+        // Grains spawned inside each other would shoot apart
+        if query.iter().any(|other| other.0.distance(pos) < 2. * radius) {
+            continue;
+        }
+        let mut grain = commands.spawn((
+            PbrBundle {
+                mesh: materials.sphere.clone(),
+                material: if granular { materials.sand.clone() } else { materials.marble.clone() },
+                transform: Transform {
+                    scale: Vec3::splat(radius),
+                    translation: pos.extend(0.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ParticleBundle {
+                collider: CircleCollider { radius },
+                inertia: Inertia::circle(1., radius),
+                restitution: Restitution(0.),
+                ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+            },
+        ));
+        if granular {
+            grain.insert(Granular::default());
+        }
+    }
+}
+
+fn despawn_fallen(mut commands: Commands, query: Query<(Entity, &Pos)>) {
+    for (entity, pos) in query.iter() {
+        if pos.0.y < -20. {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+    render::render_resource::PrimitiveTopology,
+};
+use bevy_particle_system::*;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa { samples: 4 })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(XPBDPlugin::default())
+        .add_startup_system(spawn_camera)
+        .add_startup_system(spawn_blobs)
+        .add_system(spawn_marbles)
+        .add_system(update_blob_meshes)
+        .run();
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::prelude::Projection::Orthographic(OrthographicProjection {
+            scale: 0.01,
+            ..Default::default()
+        }),
+        ..Camera3dBundle::default()
+    });
+}
+
+#[derive(Resource)]
+struct Materials {
+    blue: Handle<StandardMaterial>,
+    sphere: Handle<Mesh>,
+}
+
+/// Closed line strip drawn around a blob
+#[derive(Component)]
+struct BlobMesh {
+    soft_body: SoftBody,
+    mesh: Handle<Mesh>,
+}
+
+fn spawn_blobs(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+
+    let size = Vec2::new(10., 2.);
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE))),
+            material: blue.clone(),
+            transform: Transform::from_scale(size.extend(1.)),
+            ..Default::default()
+        })
+        .insert(StaticBoxBundle {
+            pos: Pos(Vec2::new(0., -4.)),
+            collider: BoxCollider { size },
+            ..Default::default()
+        });
+
+    // A firm blob, a squishy one and one that is slightly overinflated
+    let blobs = [
+        SoftBodyBuilder::new(Vec2::new(-2.5, 0.), 0.8),
+        SoftBodyBuilder::new(Vec2::new(0., 1.), 0.8).area_compliance(0.002),
+        SoftBodyBuilder::new(Vec2::new(2.5, 0.), 0.8)
+            .edge_compliance(0.001)
+            .pressure(1.2),
+    ];
+    for builder in blobs {
+        let soft_body = builder.spawn(&mut commands);
+        let mesh = meshes.add(Mesh::new(PrimitiveTopology::LineStrip));
+        commands.spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: blue.clone(),
+                ..Default::default()
+            },
+            BlobMesh { soft_body, mesh },
+        ));
+    }
+
+    commands.insert_resource(Materials {
+        blue,
+        sphere: meshes.add(Mesh::from(shape::Icosphere {
+            radius: 1.,
+            subdivisions: 4,
+        })),
+    });
+}
+
+fn update_blob_meshes(query: Query<&BlobMesh>, positions: Query<&Pos>, mut meshes: ResMut<Assets<Mesh>>) {
+    for blob_mesh in query.iter() {
+        let Some(mesh) = meshes.get_mut(&blob_mesh.mesh) else {
+            continue;
+        };
+        let mut vertices: Vec<[f32; 3]> = blob_mesh
+            .soft_body
+            .polyline(&positions)
+            .iter()
+            .map(|pos| [pos.x, pos.y, 0.])
+            .collect();
+        // Close the loop
+        if let Some(first) = vertices.first().copied() {
+            vertices.push(first);
+        }
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 0., 1.]; vertices.len()]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; vertices.len()]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+    }
+}
+
+fn spawn_marbles(mut commands: Commands, materials: Res<Materials>, time: Res<Time>, mut timer: Local<f32>) {
+    *timer -= time.delta_seconds();
+    if *timer > 0. {
+        return;
+    }
+    *timer = 0.5;
+
+    let radius = 0.15;
+    let pos = Vec2::new(rand::random::<f32>() * 6. - 3., 4.);
+    commands
+        .spawn(PbrBundle {
+            mesh: materials.sphere.clone(),
+            material: materials.blue.clone(),
+            transform: Transform {
+                scale: Vec3::splat(radius),
+                translation: pos.extend(0.),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(ParticleBundle {
+            collider: CircleCollider { radius },
+            inertia: Inertia::circle(1., radius),
+            ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+        });
+}
@@ -0,0 +1,126 @@
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_particle_system::*;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.8, 0.8, 0.9)))
+        .insert_resource(Msaa { samples: 4 })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(XPBDPlugin::default())
+        .add_startup_system(spawn_camera)
+        .add_startup_system(spawn_terrain)
+        .add_system(spawn_marbles)
+        .run();
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0., 0., 100.)),
+        projection: bevy::prelude::Projection::Orthographic(OrthographicProjection {
+            scale: 0.01,
+            ..Default::default()
+        }),
+        ..Camera3dBundle::default()
+    });
+}
+
+#[derive(Resource)]
+struct Materials {
+    blue: Handle<StandardMaterial>,
+    sphere: Handle<Mesh>,
+}
+
+fn spawn_terrain(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let blue = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.4, 0.4, 0.6),
+        unlit: true,
+        ..Default::default()
+    });
+    let quad = meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE)));
+
+    // Rolling hills made of many short segments, the marbles roll over the joints
+    let hills = ChainCollider::open(
+        (0..=80)
+            .map(|i| {
+                let x = i as f32 * 0.1 - 4.;
+                Vec2::new(x, -2. + 0.4 * (x * 2.).sin())
+            })
+            .collect(),
+    );
+    // Closed box around the level so nothing escapes
+    let boundary = ChainCollider::closed(vec![
+        Vec2::new(-4., -3.),
+        Vec2::new(4., -3.),
+        Vec2::new(4., 4.),
+        Vec2::new(-4., 4.),
+    ]);
+
+    for chain in [hills, boundary] {
+        let segment_count = if chain.closed { chain.vertices.len() } else { chain.vertices.len() - 1 };
+        for i in 0..segment_count {
+            let (a, b) = (chain.vertices[i], chain.vertices[(i + 1) % chain.vertices.len()]);
+            let ab = b - a;
+            commands.spawn(PbrBundle {
+                mesh: quad.clone(),
+                material: blue.clone(),
+                transform: Transform {
+                    scale: Vec3::new(ab.length(), 0.03, 1.),
+                    translation: ((a + b) / 2.).extend(0.),
+                    rotation: Quat::from_rotation_z(ab.y.atan2(ab.x)),
+                },
+                ..Default::default()
+            });
+        }
+        commands.spawn(StaticChainBundle {
+            collider: chain,
+            ..Default::default()
+        });
+    }
+
+    commands.insert_resource(Materials {
+        blue,
+        sphere: meshes.add(Mesh::from(shape::Icosphere {
+            radius: 1.,
+            subdivisions: 4,
+        })),
+    });
+}
+
+fn spawn_marbles(mut commands: Commands, materials: Res<Materials>, time: Res<Time>, mut timer: Local<(f32, u32)>) {
+    let (timer, count) = &mut *timer;
+    *timer -= time.delta_seconds();
+    if *timer > 0. || *count >= 100 {
+        return;
+    }
+    *timer = 0.2;
+    *count += 1;
+
+    let radius = 0.1;
+    let pos = Vec2::new(-3.5, 3.);
+    let vel = Vec2::new(3., 0.);
+    commands
+        .spawn(PbrBundle {
+            mesh: materials.sphere.clone(),
+            material: materials.blue.clone(),
+            transform: Transform {
+                scale: Vec3::splat(radius),
+                translation: pos.extend(0.),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(ParticleBundle {
+            collider: CircleCollider { radius },
+            inertia: Inertia::circle(1., radius),
+            ..ParticleBundle::new_with_pos_and_vel(pos, vel)
+        });
+}
@@ -0,0 +1,157 @@
+use bevy::{prelude::*, utils::HashMap};
+
+/// A circle as seen by the broad phase. `margin` is how far the circle may travel
+/// during one physics step, so pairs found here stay valid for every substep.
+#[derive(Debug, Clone, Copy)]
+pub struct BroadPhaseProxy {
+    pub entity: Entity,
+    pub pos: Vec2,
+    pub radius: f32,
+    pub margin: f32,
+}
+
+impl BroadPhaseProxy {
+    /// Largest distance from `pos` at which this proxy can still collide
+    pub fn extent(&self) -> f32 {
+        self.radius + self.margin
+    }
+
+    /// Exact pair test shared by all broad phases
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let ab = other.pos - self.pos;
+        let safety_margin_sqr = self.margin * self.margin + other.margin * other.margin;
+        let combined_radius = self.radius + other.radius + safety_margin_sqr.sqrt();
+        ab.length_squared() < combined_radius * combined_radius
+    }
+}
+
+/// Orders a pair so the larger entity comes first, matching the pairs the brute force
+/// broad phase has always produced
+fn ordered_pair(a: Entity, b: Entity) -> (Entity, Entity) {
+    if a > b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Finds the pairs of proxies that may collide during the next physics step
+pub trait BroadPhase: Send + Sync + 'static {
+    fn collect_pairs(&mut self, proxies: &[BroadPhaseProxy], pairs: &mut Vec<(Entity, Entity)>);
+}
+
+/// The broad phase used by `collect_collision_pairs`. Defaults to a spatial hash.
+#[derive(Resource)]
+pub struct BroadPhaseMethod(pub Box<dyn BroadPhase>);
+
+impl BroadPhaseMethod {
+    pub fn new(broad_phase: impl BroadPhase) -> Self {
+        Self(Box::new(broad_phase))
+    }
+}
+
+impl Default for BroadPhaseMethod {
+    fn default() -> Self {
+        Self::new(SpatialHash::default())
+    }
+}
+
+/// Tests every proxy against every other proxy
+#[derive(Debug, Default)]
+pub struct BruteForce;
+
+impl BroadPhase for BruteForce {
+    fn collect_pairs(&mut self, proxies: &[BroadPhaseProxy], pairs: &mut Vec<(Entity, Entity)>) {
+        for (i, a) in proxies.iter().enumerate() {
+            for b in &proxies[i + 1..] {
+                if a.overlaps(b) {
+                    pairs.push(ordered_pair(a.entity, b.entity));
+                }
+            }
+        }
+    }
+}
+
+/// Uniform grid stored in a hash map. The cell size is the largest proxy diameter
+/// including its safety margin, so only the neighbouring cells have to be searched.
+#[derive(Debug, Default)]
+pub struct SpatialHash {
+    cells: HashMap<IVec2, Vec<usize>>,
+}
+
+impl BroadPhase for SpatialHash {
+    fn collect_pairs(&mut self, proxies: &[BroadPhaseProxy], pairs: &mut Vec<(Entity, Entity)>) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+
+        let max_extent = proxies.iter().map(|proxy| proxy.extent()).fold(0., f32::max);
+        if max_extent <= 0. {
+            return;
+        }
+        let cell_size = 2. * max_extent;
+        let cell_of = |pos: Vec2| (pos / cell_size).floor().as_ivec2();
+
+        for (i, proxy) in proxies.iter().enumerate() {
+            self.cells.entry(cell_of(proxy.pos)).or_default().push(i);
+        }
+
+        for a in proxies {
+            let cell = cell_of(a.pos);
+            for x in -1..=1 {
+                for y in -1..=1 {
+                    let Some(neighbours) = self.cells.get(&(cell + IVec2::new(x, y))) else {
+                        continue;
+                    };
+                    for b in neighbours.iter().map(|&j| &proxies[j]) {
+                        // Each pair is visited from both sides, only keep one of them
+                        if a.entity > b.entity && a.overlaps(b) {
+                            pairs.push((a.entity, b.entity));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Drop cells that stayed empty so the map doesn't grow forever
+        self.cells.retain(|_, cell| !cell.is_empty());
+    }
+}
+
+/// Sorts proxies along the x axis and only tests proxies whose intervals overlap.
+/// The order is kept between steps, so the sort is nearly linear for coherent motion.
+#[derive(Debug, Default)]
+pub struct SweepAndPrune {
+    order: Vec<usize>,
+}
+
+impl BroadPhase for SweepAndPrune {
+    fn collect_pairs(&mut self, proxies: &[BroadPhaseProxy], pairs: &mut Vec<(Entity, Entity)>) {
+        if self.order.len() != proxies.len() {
+            self.order = (0..proxies.len()).collect();
+        }
+        let min_x = |i: usize| proxies[i].pos.x - proxies[i].extent();
+        // Insertion sort, cheap when the order barely changed since the last step
+        for i in 1..self.order.len() {
+            let mut j = i;
+            while j > 0 && min_x(self.order[j - 1]) > min_x(self.order[j]) {
+                self.order.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+
+        for (i, &index_a) in self.order.iter().enumerate() {
+            let a = &proxies[index_a];
+            let max_x = a.pos.x + a.extent();
+            for &index_b in &self.order[i + 1..] {
+                let b = &proxies[index_b];
+                if b.pos.x - b.extent() > max_x {
+                    break;
+                }
+                if a.overlaps(b) {
+                    pairs.push(ordered_pair(a.entity, b.entity));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// Spawns a particle at rest for one of the builders
+pub(crate) fn spawn_particle(commands: &mut Commands, pos: Vec2, mass: f32, radius: f32, inertia: Inertia) -> Entity {
+    commands
+        .spawn(ParticleBundle {
+            mass: Mass(mass),
+            collider: CircleCollider { radius },
+            inertia,
+            ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+        })
+        .id()
+}
+
+/// Current positions of `points` in order, skipping any that were despawned
+pub(crate) fn polyline(points: &[Entity], positions: &Query<&Pos>) -> Vec<Vec2> {
+    points
+        .iter()
+        .filter_map(|entity| positions.get(*entity).ok())
+        .map(|pos| pos.0)
+        .collect()
+}
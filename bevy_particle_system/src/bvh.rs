@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+
+/// Axis-aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Aabb {
+    pub fn from_circle(pos: Vec2, radius: f32) -> Self {
+        Self {
+            min: pos - Vec2::splat(radius),
+            max: pos + Vec2::splat(radius),
+        }
+    }
+
+    pub fn from_box(pos: Vec2, size: Vec2) -> Self {
+        Self {
+            min: pos - size / 2.,
+            max: pos + size / 2.,
+        }
+    }
+
+    /// Smallest box containing all `points`, e.g. the corners of a rotated box
+    pub fn from_points(points: impl IntoIterator<Item = Vec2>) -> Self {
+        points.into_iter().fold(
+            Self {
+                min: Vec2::splat(f32::INFINITY),
+                max: Vec2::splat(f32::NEG_INFINITY),
+            },
+            |aabb, point| Self {
+                min: aabb.min.min(point),
+                max: aabb.max.max(point),
+            },
+        )
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) / 2.
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BvhNode {
+    Leaf { aabb: Aabb, entity: Entity },
+    Internal { aabb: Aabb, left: usize, right: usize },
+}
+
+impl BvhNode {
+    fn aabb(&self) -> &Aabb {
+        match self {
+            BvhNode::Leaf { aabb, .. } | BvhNode::Internal { aabb, .. } => aabb,
+        }
+    }
+}
+
+/// Bounding volume hierarchy over a fixed set of boxes, built top-down by splitting
+/// along the longest axis at the median
+#[derive(Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    root: Option<usize>,
+    leaf_count: usize,
+}
+
+impl Bvh {
+    pub fn build(mut leaves: Vec<(Entity, Aabb)>) -> Self {
+        let mut nodes = Vec::with_capacity(leaves.len() * 2);
+        let leaf_count = leaves.len();
+        let root = (!leaves.is_empty()).then(|| Self::build_recursive(&mut nodes, &mut leaves));
+        Self {
+            nodes,
+            root,
+            leaf_count,
+        }
+    }
+
+    fn build_recursive(nodes: &mut Vec<BvhNode>, leaves: &mut [(Entity, Aabb)]) -> usize {
+        if let [(entity, aabb)] = leaves {
+            nodes.push(BvhNode::Leaf {
+                aabb: *aabb,
+                entity: *entity,
+            });
+            return nodes.len() - 1;
+        }
+
+        let centers = leaves.iter().fold(
+            Aabb {
+                min: Vec2::splat(f32::INFINITY),
+                max: Vec2::splat(f32::NEG_INFINITY),
+            },
+            |bounds, (_, aabb)| bounds.union(&Aabb::from_circle(aabb.center(), 0.)),
+        );
+        let extents = centers.max - centers.min;
+        let axis = if extents.x > extents.y { 0 } else { 1 };
+
+        let mid = leaves.len() / 2;
+        leaves.select_nth_unstable_by(mid, |(_, a), (_, b)| {
+            a.center()[axis].total_cmp(&b.center()[axis])
+        });
+        let (left_leaves, right_leaves) = leaves.split_at_mut(mid);
+        let left = Self::build_recursive(nodes, left_leaves);
+        let right = Self::build_recursive(nodes, right_leaves);
+
+        nodes.push(BvhNode::Internal {
+            aabb: nodes[left].aabb().union(nodes[right].aabb()),
+            left,
+            right,
+        });
+        nodes.len() - 1
+    }
+
+    /// Number of entities in the tree
+    pub fn len(&self) -> usize {
+        self.leaf_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaf_count == 0
+    }
+
+    /// Calls `f` for every entity whose box intersects `aabb`
+    pub fn query(&self, aabb: &Aabb, mut f: impl FnMut(Entity)) {
+        let Some(root) = self.root else {
+            return;
+        };
+        let mut stack = vec![root];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.aabb().intersects(aabb) {
+                continue;
+            }
+            match node {
+                BvhNode::Leaf { entity, .. } => f(*entity),
+                BvhNode::Internal { left, right, .. } => {
+                    stack.push(*right);
+                    stack.push(*left);
+                }
+            }
+        }
+    }
+}
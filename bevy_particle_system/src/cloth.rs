@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+
+use crate::{builder::spawn_particle, *};
+
+/// A sheet spawned by `ClothBuilder`. Lives on its own entity.
+#[derive(Component, Debug, Clone)]
+pub struct Cloth {
+    pub columns: usize,
+    pub rows: usize,
+    /// Particles and pinned anchors row by row, starting at the top left corner
+    pub points: Vec<Entity>,
+}
+
+impl Cloth {
+    pub fn point(&self, column: usize, row: usize) -> Entity {
+        self.points[row * self.columns + column]
+    }
+}
+
+/// Spawns a grid of particles hanging down from `top_left`. Neighbours are linked by
+/// structural constraints, diagonals by shear constraints and points two apart by bending
+/// constraints, all with their own compliance.
+#[derive(Debug, Clone)]
+pub struct ClothBuilder {
+    top_left: Vec2,
+    columns: usize,
+    rows: usize,
+    spacing: f32,
+    particle_radius: Option<f32>,
+    mass: f32,
+    structural_compliance: f32,
+    shear_compliance: f32,
+    bending_compliance: f32,
+    tear_force: Option<f32>,
+    pinned: Vec<(usize, usize)>,
+}
+
+impl ClothBuilder {
+    pub fn new(top_left: Vec2, columns: usize, rows: usize, spacing: f32) -> Self {
+        assert!(columns >= 2 && rows >= 2, "a cloth needs at least two columns and rows");
+        Self {
+            top_left,
+            columns,
+            rows,
+            spacing,
+            particle_radius: None,
+            mass: 0.1,
+            structural_compliance: 0.,
+            shear_compliance: 0.1,
+            bending_compliance: 0.1,
+            tear_force: None,
+            pinned: vec![],
+        }
+    }
+
+    /// Radius of the nodes. Defaults to 0.45 of the grid spacing, so the sheet catches
+    /// bodies landing on it between its nodes as well as on them.
+    pub fn particle_radius(mut self, radius: f32) -> Self {
+        self.particle_radius = Some(radius);
+        self
+    }
+
+    /// Mass of each node, 0.1 by default so the cloth stays light next to what it drapes over
+    pub fn mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    /// Inverse stiffness of the links between horizontal and vertical neighbours
+    pub fn structural_compliance(mut self, compliance: f32) -> Self {
+        self.structural_compliance = compliance;
+        self
+    }
+
+    /// Inverse stiffness of the diagonal links, which keep the cells from shearing
+    pub fn shear_compliance(mut self, compliance: f32) -> Self {
+        self.shear_compliance = compliance;
+        self
+    }
+
+    /// Inverse stiffness of the links between points two apart, which resist folding
+    pub fn bending_compliance(mut self, compliance: f32) -> Self {
+        self.bending_compliance = compliance;
+        self
+    }
+
+    /// Links tear once they pull harder than `max_force`
+    pub fn tear_force(mut self, max_force: f32) -> Self {
+        self.tear_force = Some(max_force);
+        self
+    }
+
+    /// Replaces the particle at `column` and `row` with a static anchor
+    pub fn pin(mut self, column: usize, row: usize) -> Self {
+        self.pinned.push((column, row));
+        self
+    }
+
+    pub fn spawn(self, commands: &mut Commands) -> Cloth {
+        let particle_radius = self.particle_radius.unwrap_or(0.45 * self.spacing);
+        let mut points = Vec::with_capacity(self.columns * self.rows);
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let pos = self.top_left + Vec2::new(column as f32, -(row as f32)) * self.spacing;
+                let point = if self.pinned.contains(&(column, row)) {
+                    commands.spawn(Pos(pos)).id()
+                } else {
+                    // Spinning nodes would roll like ball bearings instead of letting the
+                    // cloth grip what it lies on
+                    spawn_particle(commands, pos, self.mass, particle_radius, Inertia(f32::INFINITY))
+                };
+                points.push(point);
+            }
+        }
+
+        let cloth = Cloth {
+            columns: self.columns,
+            rows: self.rows,
+            points,
+        };
+
+        let links = [
+            (IVec2::new(1, 0), self.structural_compliance),
+            (IVec2::new(0, 1), self.structural_compliance),
+            (IVec2::new(1, 1), self.shear_compliance),
+            (IVec2::new(-1, 1), self.shear_compliance),
+            (IVec2::new(2, 0), self.bending_compliance),
+            (IVec2::new(0, 2), self.bending_compliance),
+        ];
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                for (offset, compliance) in links {
+                    let other = IVec2::new(column as i32, row as i32) + offset;
+                    if other.x < 0 || other.x >= self.columns as i32 || other.y >= self.rows as i32 {
+                        continue;
+                    }
+                    let mut link = commands.spawn(DistanceConstraint {
+                        a: cloth.point(column, row),
+                        b: cloth.point(other.x as usize, other.y as usize),
+                        rest_length: offset.as_vec2().length() * self.spacing,
+                        compliance,
+                    });
+                    if let Some(max_force) = self.tear_force {
+                        link.insert(Tearable { max_force });
+                    }
+                }
+            }
+        }
+
+        commands.spawn(cloth.clone());
+        cloth
+    }
+}
@@ -0,0 +1,110 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::*;
+
+/// Two bodies touching during a physics step. For a dynamic body hitting a static
+/// one, `entity_a` is the dynamic body.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Collision {
+    pub entity_a: Entity,
+    pub entity_b: Entity,
+    /// Points from `entity_a` towards `entity_b`, taken from the deepest contact point
+    pub normal: Vec2,
+    /// Deepest the bodies overlapped during the step
+    pub penetration_depth: f32,
+    /// Total impulse along the normal that kept the bodies from overlapping during the
+    /// step, without the extra bounce from restitution. A body resting on the ground gets
+    /// its weight times the timestep, a hard hit about its mass times its impact speed.
+    pub impulse: f32,
+}
+
+/// Sent after the first physics step in which two bodies touch
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionStarted(pub Collision);
+
+/// Sent after every further physics step in which the two bodies touch, until the
+/// collision ends
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionOngoing(pub Collision);
+
+/// Sent after the first physics step in which two bodies no longer touch, or once they
+/// have stayed apart for `CollisionEndDelay` if one is set, carrying the last step they
+/// touched. Sent right away when one of them is despawned, so the entities
+/// may no longer exist.
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionEnded(pub Collision);
+
+/// Collisions found during the current physics step, and the ones that started and
+/// haven't ended yet with the seconds their bodies have been apart, keyed by the pair
+/// of entities
+#[derive(Debug, Default, Resource)]
+pub(crate) struct StepCollisions {
+    current: HashMap<(Entity, Entity), Collision>,
+    active: HashMap<(Entity, Entity), (Collision, f32)>,
+}
+
+/// Folds the contacts of every substep into the collisions of the step, since each
+/// substep clears them
+pub(crate) fn collect_step_collisions(
+    contacts: Res<Contacts>,
+    static_contacts: Res<StaticContacts>,
+    mut collisions: ResMut<StepCollisions>,
+) {
+    for contact in contacts.0.iter().chain(&static_contacts.0) {
+        let collision = collisions
+            .current
+            .entry((contact.entity_a, contact.entity_b))
+            .or_insert(Collision {
+                entity_a: contact.entity_a,
+                entity_b: contact.entity_b,
+                normal: contact.normal,
+                penetration_depth: 0.,
+                impulse: 0.,
+            });
+        if contact.penetration_depth > collision.penetration_depth {
+            collision.penetration_depth = contact.penetration_depth;
+            collision.normal = contact.normal;
+        }
+        collision.impulse += contact.normal_impulse;
+    }
+}
+
+/// Compares the collisions of this step with the active ones. The events of a step
+/// are sent in entity order, so they come out the same in every run.
+pub(crate) fn send_collision_events(
+    mut collisions: ResMut<StepCollisions>,
+    bodies: Query<(), With<Pos>>,
+    timestep: Res<Timestep>,
+    end_delay: Res<CollisionEndDelay>,
+    mut started: EventWriter<CollisionStarted>,
+    mut ongoing: EventWriter<CollisionOngoing>,
+    mut ended: EventWriter<CollisionEnded>,
+) {
+    let StepCollisions { current, active } = &mut *collisions;
+
+    let mut gone = Vec::new();
+    active.retain(|pair, (collision, apart)| {
+        if current.contains_key(pair) {
+            return true;
+        }
+        *apart += timestep.0;
+        let despawned = !bodies.contains(pair.0) || !bodies.contains(pair.1);
+        if *apart >= end_delay.0 || despawned {
+            gone.push((*pair, *collision));
+            return false;
+        }
+        true
+    });
+    gone.sort_unstable_by_key(|(pair, _)| *pair);
+
+    let mut touching: Vec<_> = current.drain().collect();
+    touching.sort_unstable_by_key(|(pair, _)| *pair);
+    for (pair, collision) in touching {
+        if active.insert(pair, (collision, 0.)).is_some() {
+            ongoing.send(CollisionOngoing(collision));
+        } else {
+            started.send(CollisionStarted(collision));
+        }
+    }
+    ended.send_batch(gone.into_iter().map(|(_, collision)| CollisionEnded(collision)));
+}
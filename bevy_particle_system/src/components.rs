@@ -1,12 +1,13 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Component, Debug, Default)]
+#[derive(Component, Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Pos(pub Vec2);
 
-#[derive(Component, Debug, Default)]
+#[derive(Component, Debug, Default, Clone, Serialize, Deserialize)]
 pub struct PrevPos(pub Vec2);
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Mass(pub f32);
 
 impl Default for Mass {
@@ -15,7 +16,7 @@ impl Default for Mass {
     }
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct CircleCollider {
     pub radius: f32,
 }
@@ -26,13 +27,70 @@ impl Default for CircleCollider {
     }
 }
 
-#[derive(Component, Debug, Default)]
-pub struct Vel(pub(crate) Vec2);
+#[derive(Component, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Vel(pub Vec2);
 
 #[derive(Component, Debug, Default)]
 pub struct PreSolveVel(pub(crate) Vec2);
 
-#[derive(Component, Debug)]
+/// Orientation in radians, counterclockwise
+#[derive(Component, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Rot(pub f32);
+
+#[derive(Component, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PrevRot(pub f32);
+
+/// `Pos` at the start of the latest physics step, only kept up to date with `TransformSync::Interpolated`
+#[derive(Component, Debug, Default)]
+pub struct PrevStepPos(pub Vec2);
+
+/// `Rot` at the start of the latest physics step, only kept up to date with `TransformSync::Interpolated`
+#[derive(Component, Debug, Default)]
+pub struct PrevStepRot(pub f32);
+
+/// Angular velocity in radians per second, counterclockwise
+#[derive(Component, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AngVel(pub f32);
+
+#[derive(Component, Debug, Default)]
+pub struct PreSolveAngVel(pub(crate) f32);
+
+/// Moment of inertia around the center of mass
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Inertia(pub f32);
+
+impl Inertia {
+    /// Moment of inertia of a solid disc
+    pub fn circle(mass: f32, radius: f32) -> Self {
+        Self(0.5 * mass * radius * radius)
+    }
+
+    /// Moment of inertia of a solid rectangle
+    pub fn rectangle(mass: f32, size: Vec2) -> Self {
+        Self(mass * size.length_squared() / 12.)
+    }
+
+    /// Moment of inertia of a solid polygon around its position, which should be the
+    /// polygon's centroid
+    pub fn polygon(mass: f32, polygon: &ConvexPolygonCollider) -> Self {
+        let (numerator, denominator) = polygon.edges().fold((0., 0.), |(numerator, denominator), (a, b)| {
+            let cross = a.perp_dot(b);
+            (
+                numerator + cross * (a.dot(a) + a.dot(b) + b.dot(b)),
+                denominator + cross,
+            )
+        });
+        Self(mass * numerator / (6. * denominator))
+    }
+}
+
+impl Default for Inertia {
+    fn default() -> Self {
+        Self::circle(Mass::default().0, CircleCollider::default().radius)
+    }
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Restitution(pub f32);
 
 impl Default for Restitution {
@@ -41,8 +99,23 @@ impl Default for Restitution {
     }
 }
 
+/// Coulomb friction coefficients. Contacts use the average of both bodies' coefficients.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Friction {
+    pub static_coeff: f32,
+    pub dynamic_coeff: f32,
+}
 
-#[derive(Component, Debug)]
+impl Default for Friction {
+    fn default() -> Self {
+        Self {
+            static_coeff: 0.4,
+            dynamic_coeff: 0.3,
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct BoxCollider {
     pub size: Vec2,
 }
@@ -51,4 +124,218 @@ impl Default for BoxCollider {
     fn default() -> Self {
         Self { size: Vec2::ONE }
     }
-}
\ No newline at end of file
+}
+
+impl BoxCollider {
+    /// Counterclockwise corners of the box centered at `pos` and rotated by `rot`
+    pub fn vertices(&self, pos: Vec2, rot: f32) -> Vec<Vec2> {
+        let half_extents = self.size / 2.;
+        let rotation = Vec2::from_angle(rot);
+        [
+            Vec2::new(-half_extents.x, -half_extents.y),
+            Vec2::new(half_extents.x, -half_extents.y),
+            Vec2::new(half_extents.x, half_extents.y),
+            Vec2::new(-half_extents.x, half_extents.y),
+        ]
+        .into_iter()
+        .map(|corner| pos + rotation.rotate(corner))
+        .collect()
+    }
+}
+
+/// Convex polygon with vertices relative to the body's position. Dynamic bodies rotate
+/// around their position, so their vertices should be centered on the centroid.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct ConvexPolygonCollider {
+    /// Counterclockwise vertices, use `ConvexPolygonCollider::new` to fix up clockwise ones.
+    /// A polygon with fewer than three vertices doesn't collide with anything.
+    pub vertices: Vec<Vec2>,
+}
+
+impl Default for ConvexPolygonCollider {
+    fn default() -> Self {
+        Self::new(BoxCollider::default().vertices(Vec2::ZERO, 0.))
+    }
+}
+
+impl ConvexPolygonCollider {
+    pub fn new(mut vertices: Vec<Vec2>) -> Self {
+        assert!(vertices.len() >= 3, "a polygon needs at least three vertices");
+        let polygon = Self { vertices: vertices.clone() };
+        if polygon.signed_area() < 0. {
+            vertices.reverse();
+        }
+        Self { vertices }
+    }
+
+    /// Polygon with `sides` corners on a circle of `radius`
+    pub fn regular(sides: usize, radius: f32) -> Self {
+        let step = std::f32::consts::TAU / sides as f32;
+        Self::new(
+            (0..sides)
+                .map(|i| Vec2::from_angle(step * i as f32) * radius)
+                .collect(),
+        )
+    }
+
+    /// Pairs of consecutive vertices, wrapping around to the first
+    pub fn edges(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(a, b)| (*a, *b))
+    }
+
+    /// Positive for counterclockwise vertices
+    fn signed_area(&self) -> f32 {
+        self.edges().map(|(a, b)| a.perp_dot(b)).sum::<f32>() / 2.
+    }
+
+    pub fn centroid(&self) -> Vec2 {
+        let sum = self
+            .edges()
+            .map(|(a, b)| (a + b) * a.perp_dot(b))
+            .fold(Vec2::ZERO, |sum, v| sum + v);
+        sum / (6. * self.signed_area())
+    }
+
+    /// Distance from the position to the furthest vertex
+    pub fn bounding_radius(&self) -> f32 {
+        self.vertices.iter().map(|v| v.length()).fold(0., f32::max)
+    }
+
+    /// Vertices of the polygon placed at `pos` and rotated by `rot`
+    pub fn world_vertices(&self, pos: Vec2, rot: f32) -> Vec<Vec2> {
+        let rotation = Vec2::from_angle(rot);
+        self.vertices.iter().map(|v| pos + rotation.rotate(*v)).collect()
+    }
+}
+
+/// Capsule standing along the local y axis like bevy's capsule mesh: a segment of `length`
+/// grown by `radius`. Rotate it with `Rot`.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct CapsuleCollider {
+    pub length: f32,
+    pub radius: f32,
+}
+
+impl Default for CapsuleCollider {
+    fn default() -> Self {
+        Self { length: 1., radius: 0.1 }
+    }
+}
+
+impl CapsuleCollider {
+    /// Centers of the two end caps of the capsule placed at `pos` and rotated by `rot`
+    pub fn ends(&self, pos: Vec2, rot: f32) -> [Vec2; 2] {
+        let half_axis = Vec2::from_angle(rot).rotate(Vec2::Y * self.length / 2.);
+        [pos - half_axis, pos + half_axis]
+    }
+}
+
+/// Line segment with no thickness from `a` to `b`, relative to the body's position
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentCollider {
+    pub a: Vec2,
+    pub b: Vec2,
+}
+
+impl Default for SegmentCollider {
+    fn default() -> Self {
+        Self {
+            a: Vec2::new(-0.5, 0.),
+            b: Vec2::new(0.5, 0.),
+        }
+    }
+}
+
+impl SegmentCollider {
+    /// End points of the segment placed at `pos` and rotated by `rot`
+    pub fn ends(&self, pos: Vec2, rot: f32) -> [Vec2; 2] {
+        let rotation = Vec2::from_angle(rot);
+        [pos + rotation.rotate(self.a), pos + rotation.rotate(self.b)]
+    }
+}
+
+/// Polyline of segments for level boundaries and terrain, relative to the body's position.
+/// A closed chain also connects the last vertex to the first one.
+#[derive(Component, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ChainCollider {
+    pub vertices: Vec<Vec2>,
+    pub closed: bool,
+}
+
+impl ChainCollider {
+    pub fn open(vertices: Vec<Vec2>) -> Self {
+        Self { vertices, closed: false }
+    }
+
+    pub fn closed(vertices: Vec<Vec2>) -> Self {
+        Self { vertices, closed: true }
+    }
+
+    /// Vertices of the chain placed at `pos` and rotated by `rot`
+    pub fn world_vertices(&self, pos: Vec2, rot: f32) -> Vec<Vec2> {
+        let rotation = Vec2::from_angle(rot);
+        self.vertices.iter().map(|v| pos + rotation.rotate(*v)).collect()
+    }
+}
+
+/// Keeps the centers of two bodies `rest_length` apart. Spawn it on its own entity.
+/// `compliance` is the inverse stiffness, zero makes the link rigid. Either body may be
+/// static, which pins the other one to it.
+#[derive(Component, Debug)]
+pub struct DistanceConstraint {
+    pub a: Entity,
+    pub b: Entity,
+    pub rest_length: f32,
+    pub compliance: f32,
+}
+
+/// Removes the constraint on the same entity once it pulls harder than `max_force`
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Tearable {
+    pub max_force: f32,
+}
+
+/// Keeps the area enclosed by a counterclockwise ring of bodies at `rest_area`, which
+/// acts like the pressure inside a blob. Spawn it on its own entity.
+#[derive(Component, Debug, Clone)]
+pub struct AreaConstraint {
+    pub ring: Vec<Entity>,
+    pub rest_area: f32,
+    pub compliance: f32,
+}
+
+/// Makes a particle part of a liquid that keeps its density at `FluidSettings::rest_density`.
+/// Fluid particles still collide like any other particle, so give them a radius below half
+/// the rest spacing.
+#[derive(Component, Debug, Default)]
+pub struct Fluid;
+
+/// Makes a particle behave like a grain of sand or powder. Touching grains stick together
+/// and resist rolling over each other, so piles keep a steep slope instead of spreading flat.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Granular {
+    /// Force pulling two grains together while the gap between them is below `cohesion_range`
+    pub cohesion: f32,
+    /// Largest gap between two grains that still holds them together
+    pub cohesion_range: f32,
+    /// Like a friction coefficient, but for the grains rolling over each other
+    pub rolling_resistance: f32,
+}
+
+impl Default for Granular {
+    fn default() -> Self {
+        Self {
+            cohesion: 5.,
+            cohesion_range: 0.02,
+            rolling_resistance: 0.3,
+        }
+    }
+}
+
+/// A body without `Mass` that moves with its `Vel` and `AngVel`, which user code sets.
+/// It pushes dynamic bodies around but nothing pushes it back, e.g. paddles or platforms.
+#[derive(Component, Debug, Default)]
+pub struct Kinematic;
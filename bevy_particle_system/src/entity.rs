@@ -10,14 +10,20 @@ pub struct ParticleBundle {
     pub collider: CircleCollider,
     pub vel: Vel,
     pub pre_solve_vel: PreSolveVel,
+    pub rot: Rot,
+    pub prev_rot: PrevRot,
+    pub ang_vel: AngVel,
+    pub pre_solve_ang_vel: PreSolveAngVel,
+    pub inertia: Inertia,
     pub restitution: Restitution,
+    pub friction: Friction,
 }
 
 impl ParticleBundle {
     pub fn new_with_pos_and_vel(pos: Vec2, vel: Vec2) -> Self {
         Self {
             pos: Pos(pos),
-            prev_pos: PrevPos(pos - vel * DELTA_TIME),
+            prev_pos: PrevPos(pos),
             vel: Vel(vel),
             ..Default::default()
         }
@@ -29,11 +35,175 @@ pub struct StaticCircleBundle {
     pub pos: Pos,
     pub collider: CircleCollider,
     pub restitution: Restitution,
+    pub friction: Friction,
 }
 
+/// A box that never moves. `rot` tilts it counterclockwise, e.g. to make a ramp.
 #[derive(Bundle, Default)]
 pub struct StaticBoxBundle {
     pub pos: Pos,
+    pub rot: Rot,
     pub collider: BoxCollider,
     pub restitution: Restitution,
-}
\ No newline at end of file
+    pub friction: Friction,
+}
+
+/// A box with mass that falls, stacks and can be pushed around
+#[derive(Bundle)]
+pub struct DynamicBoxBundle {
+    pub pos: Pos,
+    pub prev_pos: PrevPos,
+    pub mass: Mass,
+    pub collider: BoxCollider,
+    pub vel: Vel,
+    pub pre_solve_vel: PreSolveVel,
+    pub rot: Rot,
+    pub prev_rot: PrevRot,
+    pub ang_vel: AngVel,
+    pub pre_solve_ang_vel: PreSolveAngVel,
+    pub inertia: Inertia,
+    pub restitution: Restitution,
+    pub friction: Friction,
+}
+
+impl Default for DynamicBoxBundle {
+    fn default() -> Self {
+        let mass = Mass::default();
+        let collider = BoxCollider::default();
+        Self {
+            inertia: Inertia::rectangle(mass.0, collider.size),
+            pos: Default::default(),
+            prev_pos: Default::default(),
+            mass,
+            collider,
+            vel: Default::default(),
+            pre_solve_vel: Default::default(),
+            rot: Default::default(),
+            prev_rot: Default::default(),
+            ang_vel: Default::default(),
+            pre_solve_ang_vel: Default::default(),
+            restitution: Default::default(),
+            friction: Default::default(),
+        }
+    }
+}
+
+impl DynamicBoxBundle {
+    pub fn new_with_pos_and_vel(pos: Vec2, vel: Vec2) -> Self {
+        Self {
+            pos: Pos(pos),
+            prev_pos: PrevPos(pos),
+            vel: Vel(vel),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Bundle, Default)]
+pub struct StaticPolygonBundle {
+    pub pos: Pos,
+    pub rot: Rot,
+    pub collider: ConvexPolygonCollider,
+    pub restitution: Restitution,
+    pub friction: Friction,
+}
+
+/// A convex polygon with mass. Its inertia has to match the collider, see `Inertia::polygon`.
+#[derive(Bundle)]
+pub struct DynamicPolygonBundle {
+    pub pos: Pos,
+    pub prev_pos: PrevPos,
+    pub mass: Mass,
+    pub collider: ConvexPolygonCollider,
+    pub vel: Vel,
+    pub pre_solve_vel: PreSolveVel,
+    pub rot: Rot,
+    pub prev_rot: PrevRot,
+    pub ang_vel: AngVel,
+    pub pre_solve_ang_vel: PreSolveAngVel,
+    pub inertia: Inertia,
+    pub restitution: Restitution,
+    pub friction: Friction,
+}
+
+impl Default for DynamicPolygonBundle {
+    fn default() -> Self {
+        let mass = Mass::default();
+        let collider = ConvexPolygonCollider::default();
+        Self {
+            inertia: Inertia::polygon(mass.0, &collider),
+            pos: Default::default(),
+            prev_pos: Default::default(),
+            mass,
+            collider,
+            vel: Default::default(),
+            pre_solve_vel: Default::default(),
+            rot: Default::default(),
+            prev_rot: Default::default(),
+            ang_vel: Default::default(),
+            pre_solve_ang_vel: Default::default(),
+            restitution: Default::default(),
+            friction: Default::default(),
+        }
+    }
+}
+
+impl DynamicPolygonBundle {
+    pub fn new_with_pos_and_vel(pos: Vec2, vel: Vec2) -> Self {
+        Self {
+            pos: Pos(pos),
+            prev_pos: PrevPos(pos),
+            vel: Vel(vel),
+            ..Default::default()
+        }
+    }
+}
+
+/// A wall or pipe with rounded ends, thin capsules don't let fast particles tunnel through
+/// the way thin boxes do
+#[derive(Bundle, Default)]
+pub struct StaticCapsuleBundle {
+    pub pos: Pos,
+    pub rot: Rot,
+    pub collider: CapsuleCollider,
+    pub restitution: Restitution,
+    pub friction: Friction,
+}
+
+#[derive(Bundle, Default)]
+pub struct StaticSegmentBundle {
+    pub pos: Pos,
+    pub rot: Rot,
+    pub collider: SegmentCollider,
+    pub restitution: Restitution,
+    pub friction: Friction,
+}
+
+/// Terrain or a level boundary made of one connected polyline
+#[derive(Bundle, Default)]
+pub struct StaticChainBundle {
+    pub pos: Pos,
+    pub rot: Rot,
+    pub collider: ChainCollider,
+    pub restitution: Restitution,
+    pub friction: Friction,
+}
+
+/// Turns any static bundle into a kinematic body moving with `vel` and `ang_vel`, e.g.
+/// `commands.spawn((StaticBoxBundle { .. }, KinematicBundle::new(Vec2::X, 0.)))`
+#[derive(Bundle, Default)]
+pub struct KinematicBundle {
+    pub kinematic: Kinematic,
+    pub vel: Vel,
+    pub ang_vel: AngVel,
+}
+
+impl KinematicBundle {
+    pub fn new(vel: Vec2, ang_vel: f32) -> Self {
+        Self {
+            kinematic: Kinematic,
+            vel: Vel(vel),
+            ang_vel: AngVel(ang_vel),
+        }
+    }
+}
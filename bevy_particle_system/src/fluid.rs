@@ -0,0 +1,197 @@
+use std::f32::consts::PI;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::*;
+
+/// Fluid particles and the fluid particles within the kernel radius of them, gathered
+/// once per step from the broad phase pairs
+#[derive(Debug, Default, Resource)]
+pub(crate) struct FluidNeighbours {
+    entities: Vec<Entity>,
+    neighbours: Vec<Vec<usize>>,
+}
+
+/// Smoothing kernel for densities
+fn poly6(r_sqr: f32, h: f32) -> f32 {
+    if r_sqr >= h * h {
+        return 0.;
+    }
+    let x = h * h - r_sqr;
+    4. / (PI * h.powi(8)) * x * x * x
+}
+
+/// Gradient of the spiky kernel, which doesn't vanish when particles get close
+fn spiky_gradient(r: Vec2, h: f32) -> Vec2 {
+    let length = r.length();
+    if length >= h || length <= f32::EPSILON {
+        return Vec2::ZERO;
+    }
+    let x = h - length;
+    r / length * (-30. / (PI * h.powi(5)) * x * x)
+}
+
+pub(crate) fn collect_fluid_neighbours(
+    fluids: Query<Entity, With<Fluid>>,
+    collision_pairs: Res<CollisionPairs>,
+    mut fluid_neighbours: ResMut<FluidNeighbours>,
+    mut index: Local<HashMap<Entity, usize>>,
+) {
+    let fluid_neighbours = &mut *fluid_neighbours;
+    fluid_neighbours.entities.clear();
+    fluid_neighbours.entities.extend(fluids.iter());
+    fluid_neighbours.neighbours.resize_with(fluid_neighbours.entities.len(), Vec::new);
+    for neighbours in &mut fluid_neighbours.neighbours {
+        neighbours.clear();
+    }
+
+    index.clear();
+    index.extend(fluid_neighbours.entities.iter().enumerate().map(|(i, entity)| (*entity, i)));
+    for (a, b) in collision_pairs.0.iter() {
+        if let (Some(&i), Some(&j)) = (index.get(a), index.get(b)) {
+            fluid_neighbours.neighbours[i].push(j);
+            fluid_neighbours.neighbours[j].push(i);
+        }
+    }
+}
+
+/// State of every fluid particle for the current substep, indexed like `FluidNeighbours`
+#[derive(Default)]
+pub(crate) struct DensityScratch {
+    pos: Vec<Vec2>,
+    mass: Vec<f32>,
+    lambda: Vec<f32>,
+}
+
+/// Moves fluid particles so each one's density gets closer to the rest density
+pub(crate) fn solve_fluid_density(
+    mut fluids: Query<(&mut Pos, &Mass), With<Fluid>>,
+    fluid_neighbours: Res<FluidNeighbours>,
+    settings: Res<FluidSettings>,
+    mut scratch: Local<DensityScratch>,
+) {
+    let FluidNeighbours { entities, neighbours } = &*fluid_neighbours;
+    let h = settings.kernel_radius;
+    let rest_density = settings.rest_density;
+    let scratch = &mut *scratch;
+
+    scratch.pos.clear();
+    scratch.mass.clear();
+    for entity in entities {
+        // Particles despawned since the neighbour search are left out with zero mass
+        let (pos, mass) = fluids.get(*entity).map_or((Vec2::ZERO, 0.), |(pos, mass)| (pos.0, mass.0));
+        scratch.pos.push(pos);
+        scratch.mass.push(mass);
+    }
+
+    let (pos, mass) = (&scratch.pos, &scratch.mass);
+    scratch.lambda.clear();
+    scratch.lambda.extend((0..entities.len()).map(|i| {
+        if mass[i] == 0. {
+            return 0.;
+        }
+        let density = mass[i] * poly6(0., h)
+            + neighbours[i]
+                .iter()
+                .map(|&j| mass[j] * poly6(pos[i].distance_squared(pos[j]), h))
+                .sum::<f32>();
+        // Only push particles apart, pulling them together makes free surfaces clump
+        let constraint = (density / rest_density - 1.).max(0.);
+
+        let mut gradient_i = Vec2::ZERO;
+        let mut w_sum = 0.;
+        for &j in &neighbours[i] {
+            if mass[j] == 0. {
+                continue;
+            }
+            let gradient_j = -mass[j] / rest_density * spiky_gradient(pos[i] - pos[j], h);
+            gradient_i -= gradient_j;
+            w_sum += gradient_j.length_squared() / mass[j];
+        }
+        w_sum += gradient_i.length_squared() / mass[i];
+        -constraint / (w_sum + settings.relaxation)
+    }));
+
+    for (i, entity) in entities.iter().enumerate() {
+        if mass[i] == 0. {
+            continue;
+        }
+        let lambda = &scratch.lambda;
+        let delta = neighbours[i]
+            .iter()
+            .filter(|&&j| mass[j] > 0.)
+            .map(|&j| (mass[j] * lambda[i] + mass[i] * lambda[j]) * spiky_gradient(pos[i] - pos[j], h))
+            .fold(Vec2::ZERO, |sum, v| sum + v);
+        if let Ok((mut pos, _)) = fluids.get_mut(*entity) {
+            pos.0 += delta / (mass[i] * rest_density);
+        }
+    }
+}
+
+/// Velocities and swirl of every fluid particle, indexed like `FluidNeighbours`
+#[derive(Default)]
+pub(crate) struct VelocityScratch {
+    pos: Vec<Vec2>,
+    vel: Vec<Vec2>,
+    mass: Vec<f32>,
+    vorticity: Vec<f32>,
+}
+
+/// Smooths the velocities of fluid particles towards their neighbours' (XSPH viscosity)
+/// and adds back the swirls damping removes (vorticity confinement)
+pub(crate) fn solve_fluid_velocities(
+    mut fluids: Query<(&Pos, &mut Vel, &Mass), With<Fluid>>,
+    fluid_neighbours: Res<FluidNeighbours>,
+    settings: Res<FluidSettings>,
+    substeps: Res<SubstepCount>,
+    timestep: Res<Timestep>,
+    mut scratch: Local<VelocityScratch>,
+) {
+    let FluidNeighbours { entities, neighbours } = &*fluid_neighbours;
+    let h = settings.kernel_radius;
+    let VelocityScratch { pos, vel, mass, vorticity } = &mut *scratch;
+
+    pos.clear();
+    vel.clear();
+    mass.clear();
+    for entity in entities {
+        let (p, v, m) = fluids.get(*entity).map_or((Vec2::ZERO, Vec2::ZERO, 0.), |(pos, vel, mass)| (pos.0, vel.0, mass.0));
+        pos.push(p);
+        vel.push(v);
+        mass.push(m);
+    }
+    let volume = |j: usize| mass[j] / settings.rest_density;
+
+    vorticity.clear();
+    vorticity.extend((0..entities.len()).map(|i| {
+        neighbours[i]
+            .iter()
+            .filter(|&&j| mass[j] > 0.)
+            .map(|&j| volume(j) * (vel[j] - vel[i]).perp_dot(-spiky_gradient(pos[i] - pos[j], h)))
+            .sum::<f32>()
+    }));
+
+    let sub_dt = substeps.sub_dt(&timestep);
+    for (i, entity) in entities.iter().enumerate() {
+        if mass[i] == 0. {
+            continue;
+        }
+        let mut viscosity = Vec2::ZERO;
+        let mut vorticity_gradient = Vec2::ZERO;
+        for &j in neighbours[i].iter().filter(|&&j| mass[j] > 0.) {
+            let r = pos[i] - pos[j];
+            viscosity += volume(j) * (vel[j] - vel[i]) * poly6(r.length_squared(), h);
+            vorticity_gradient += volume(j) * vorticity[j].abs() * spiky_gradient(r, h);
+        }
+
+        let mut delta = settings.viscosity * viscosity;
+        if vorticity_gradient.length_squared() > f32::EPSILON {
+            // Pushes particles around the swirl, the 2D version of N x omega
+            let n = vorticity_gradient.normalize();
+            delta += sub_dt * settings.vorticity * Vec2::new(n.y, -n.x) * vorticity[i];
+        }
+        if let Ok((_, mut vel, _)) = fluids.get_mut(*entity) {
+            vel.0 += delta;
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// Pulls granular particles that are almost touching towards each other with a constant
+/// force. Contacts solved afterwards keep them from overlapping.
+pub(crate) fn solve_cohesion(
+    mut grains: Query<(&mut Pos, &Mass, &CircleCollider, &Granular)>,
+    collision_pairs: Res<CollisionPairs>,
+    substeps: Res<SubstepCount>,
+    timestep: Res<Timestep>,
+) {
+    let sub_dt = substeps.sub_dt(&timestep);
+    for (entity_a, entity_b) in collision_pairs.0.iter() {
+        let Ok([(mut pos_a, mass_a, circle_a, granular_a), (mut pos_b, mass_b, circle_b, granular_b)]) =
+            grains.get_many_mut([*entity_a, *entity_b])
+        else {
+            continue;
+        };
+        let ab = pos_b.0 - pos_a.0;
+        let length = ab.length();
+        let gap = length - circle_a.radius - circle_b.radius;
+        let range = (granular_a.cohesion_range + granular_b.cohesion_range) / 2.;
+        if gap > range || length <= f32::EPSILON {
+            continue;
+        }
+
+        // A force moves a body by F / m * dt^2 over one substep
+        let cohesion = (granular_a.cohesion + granular_b.cohesion) / 2.;
+        let n = ab / length;
+        pos_a.0 += n * cohesion / mass_a.0 * sub_dt * sub_dt;
+        pos_b.0 -= n * cohesion / mass_b.0 * sub_dt * sub_dt;
+    }
+}
+
+/// Rolling resistance of one contact, using the granular body's coefficient if only one
+/// of them is granular
+fn rolling_coeff(a: Option<&Granular>, b: Option<&Granular>) -> Option<f32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some((a.rolling_resistance + b.rolling_resistance) / 2.),
+        (Some(granular), None) | (None, Some(granular)) => Some(granular.rolling_resistance),
+        (None, None) => None,
+    }
+}
+
+/// Returns the change in relative angular velocity that opposes two bodies rolling over
+/// each other. Like dynamic friction, the normal force comes from the penetration depth.
+fn rolling_resistance(relative_ang_vel: f32, contact: &Contact, radius: f32, coeff: f32, sub_dt: f32) -> f32 {
+    if radius <= f32::EPSILON {
+        return 0.;
+    }
+    let max_change = coeff * contact.penetration_depth / (sub_dt * radius);
+    -relative_ang_vel.signum() * max_change.min(relative_ang_vel.abs())
+}
+
+pub(crate) fn solve_rolling_resistance(
+    mut bodies: Query<(&mut AngVel, &Inertia, Option<&Granular>), With<Mass>>,
+    statics: Query<(Option<&Granular>, Option<&AngVel>), Without<Mass>>,
+    contacts: Res<Contacts>,
+    static_contacts: Res<StaticContacts>,
+    substeps: Res<SubstepCount>,
+    timestep: Res<Timestep>,
+) {
+    let sub_dt = substeps.sub_dt(&timestep);
+    for contact in &contacts.0 {
+        let Ok([(mut ang_vel_a, inertia_a, granular_a), (mut ang_vel_b, inertia_b, granular_b)]) =
+            bodies.get_many_mut([contact.entity_a, contact.entity_b])
+        else {
+            continue;
+        };
+        let Some(coeff) = rolling_coeff(granular_a, granular_b) else {
+            continue;
+        };
+        // Effective radius of the two surfaces rolling over each other
+        let (length_a, length_b) = (contact.r_a.length(), contact.r_b.length());
+        let radius = length_a * length_b / (length_a + length_b).max(f32::EPSILON);
+
+        let delta = rolling_resistance(ang_vel_a.0 - ang_vel_b.0, contact, radius, coeff, sub_dt);
+        let inv_inertia_a = 1. / inertia_a.0;
+        let inv_inertia_b = 1. / inertia_b.0;
+        let w_sum = inv_inertia_a + inv_inertia_b;
+        if w_sum <= 0. {
+            continue;
+        }
+        let angular_impulse = delta / w_sum;
+        ang_vel_a.0 += angular_impulse * inv_inertia_a;
+        ang_vel_b.0 -= angular_impulse * inv_inertia_b;
+    }
+
+    for contact in &static_contacts.0 {
+        let Ok((mut ang_vel, _, granular)) = bodies.get_mut(contact.entity_a) else {
+            continue;
+        };
+        let Ok((static_granular, static_ang_vel)) = statics.get(contact.entity_b) else {
+            continue;
+        };
+        let Some(coeff) = rolling_coeff(granular, static_granular) else {
+            continue;
+        };
+        // Kinematic bodies can spin, everything else static can't
+        let relative_ang_vel = ang_vel.0 - static_ang_vel.map_or(0., |ang_vel| ang_vel.0);
+        ang_vel.0 += rolling_resistance(relative_ang_vel, contact, contact.r_a.length(), coeff, sub_dt);
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+type StepStartItems<'a> = (
+    Entity,
+    &'a Pos,
+    Option<&'a Rot>,
+    Option<&'a mut PrevStepPos>,
+    Option<&'a mut PrevStepRot>,
+);
+type InterpolatedItems<'a> = (
+    &'a mut Transform,
+    &'a Pos,
+    Option<&'a PrevStepPos>,
+    Option<&'a Rot>,
+    Option<&'a PrevStepRot>,
+);
+
+/// Remembers where every body starts the step, so frames in between can be
+/// blended from there to where the step ends
+pub(crate) fn record_step_start(
+    mut commands: Commands,
+    mut query: Query<StepStartItems>,
+    sync: Res<TransformSync>,
+) {
+    if *sync != TransformSync::Interpolated {
+        return;
+    }
+    for (entity, pos, rot, prev_step_pos, prev_step_rot) in query.iter_mut() {
+        match prev_step_pos {
+            Some(mut prev_step_pos) => prev_step_pos.0 = pos.0,
+            None => {
+                commands.entity(entity).insert(PrevStepPos(pos.0));
+            }
+        }
+        match (rot, prev_step_rot) {
+            (Some(rot), Some(mut prev_step_rot)) => prev_step_rot.0 = rot.0,
+            (Some(rot), None) => {
+                commands.entity(entity).insert(PrevStepRot(rot.0));
+            }
+            _ => {}
+        }
+    }
+}
+
+pub(crate) fn interpolate_transforms(
+    mut query: Query<InterpolatedItems>,
+    sync: Res<TransformSync>,
+    accumulator: Res<FixedStepAccumulator>,
+    timestep: Res<Timestep>,
+) {
+    if *sync != TransformSync::Interpolated {
+        return;
+    }
+    let alpha = accumulator.overstep(&timestep).min(1.);
+    for (mut transform, pos, prev_step_pos, rot, prev_step_rot) in query.iter_mut() {
+        // Bodies spawned since the last step have nothing to blend from yet
+        let prev_pos = prev_step_pos.map_or(pos.0, |prev| prev.0);
+        transform.translation = prev_pos.lerp(pos.0, alpha).extend(0.);
+        if let Some(rot) = rot {
+            let prev_rot = prev_step_rot.map_or(rot.0, |prev| prev.0);
+            transform.rotation = Quat::from_rotation_z(prev_rot + (rot.0 - prev_rot) * alpha);
+        }
+    }
+}
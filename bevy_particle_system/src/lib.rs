@@ -1,270 +1,1176 @@
-use bevy::{time::FixedTimestep, prelude::*};
+use bevy::{ecs::schedule::ShouldRun, prelude::*, transform::TransformSystem};
 
+mod broad_phase;
+mod builder;
+mod bvh;
+mod cloth;
+mod collision_events;
 mod components;
 mod entity;
+mod fluid;
+mod granular;
+mod interpolation;
+mod narrow_phase;
+mod physics_world;
+mod recording;
 mod resources;
+mod ron_file;
+mod rope;
+mod snapshot;
+mod soft_body;
 
+pub use broad_phase::*;
+pub use bvh::*;
+pub use cloth::*;
+pub use collision_events::*;
 pub use resources::*;
 pub use components::*;
 pub use entity::*;
+pub use physics_world::*;
+pub use recording::*;
+pub use ron_file::*;
+pub use rope::*;
+pub use snapshot::*;
+pub use soft_body::*;
+pub use narrow_phase::{closest_point_on_segment, ContactPoint};
 
+use collision_events::{collect_step_collisions, send_collision_events, StepCollisions};
+use fluid::{collect_fluid_neighbours, solve_fluid_density, solve_fluid_velocities, FluidNeighbours};
+use granular::{solve_cohesion, solve_rolling_resistance};
+use interpolation::{interpolate_transforms, record_step_start};
+use narrow_phase::WorldShape;
+use recording::{count_recorded_step, record_commands};
+use snapshot::{insert_body, BodyItems, ShapeItems};
+
+/// Default length of a fixed physics step, see `XPBDPlugin::timestep`
 pub const DELTA_TIME: f32 = 1. / 60.;
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel)]
 struct FixedUpdateStage;
 
-#[derive(Debug, Default)]
-pub struct XPBDPlugin;
+#[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel)]
+enum PhysicsStage {
+    BroadPhase,
+    Substeps,
+    Solve,
+    Sync,
+}
+
+#[derive(Debug)]
+pub struct XPBDPlugin {
+    /// Seconds simulated by each fixed physics step, stored in the `Timestep` resource.
+    /// Must be positive.
+    pub timestep: f32,
+    /// Sorts the collision pairs and hashes the state after every step, see `Deterministic`
+    pub deterministic: bool,
+}
+
+impl Default for XPBDPlugin {
+    fn default() -> Self {
+        Self {
+            timestep: DELTA_TIME,
+            deterministic: false,
+        }
+    }
+}
 
 impl Plugin for XPBDPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Gravity>()
+        check_timestep(self.timestep);
+        app.insert_resource(Timestep(self.timestep))
+            .insert_resource(Deterministic(self.deterministic))
+            .init_resource::<PhysicsRng>()
+            .init_resource::<StateHash>()
+            .init_resource::<Gravity>()
+            .init_resource::<SubstepCount>()
+            .init_resource::<BroadPhaseMethod>()
             .init_resource::<CollisionPairs>()
+            .init_resource::<StaticBvh>()
+            .init_resource::<StaticCollisionPairs>()
             .init_resource::<Contacts>()
             .init_resource::<StaticContacts>()
+            .init_resource::<FluidSettings>()
+            .init_resource::<FluidNeighbours>()
+            .init_resource::<FixedStepAccumulator>()
+            .init_resource::<TransformSync>()
+            .init_resource::<PhysicsStepping>()
+            .init_resource::<CollisionEndDelay>()
+            .init_resource::<StepCollisions>()
+            .add_event::<CollisionStarted>()
+            .add_event::<CollisionOngoing>()
+            .add_event::<CollisionEnded>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                interpolate_transforms.before(TransformSystem::TransformPropagate),
+            )
+            .add_system_to_stage(CoreStage::PostUpdate, record_commands)
             .add_stage_before(
             CoreStage::Update,
             FixedUpdateStage,
-            SystemStage::parallel()
-            .with_run_criteria(FixedTimestep::step(DELTA_TIME as f64))
-            .with_system(
-                collect_collision_pairs
-                    .label(Step::CollectCollisionPairs)
-                    .before(Step::Integrate),
+            Schedule::default()
+            .with_run_criteria(run_fixed_timestep)
+            .with_stage(
+                PhysicsStage::BroadPhase,
+                SystemStage::parallel()
+                .with_system(collect_collision_pairs.label(Step::CollectCollisionPairs))
+                .with_system(collect_fluid_neighbours.after(collect_collision_pairs))
+                .with_system(update_static_bvh.label(Step::UpdateStaticBvh))
+                .with_system(record_step_start)
+                .with_system(
+                    collect_static_collision_pairs
+                        .label(Step::CollectCollisionPairs)
+                        .after(Step::UpdateStaticBvh),
+                ),
             )
-            .with_system(integrate.label(Step::Integrate))
-            .with_system(clear_contacts.before(Step::SolvePositions)) 
-            .with_system_set(
-                SystemSet::new()
-                    .label(Step::SolvePositions)
-                    .after(Step::Integrate)
-                    .with_system(solve_pos)
-                    .with_system(solve_pos_statics)
-                    .with_system(solve_pos_static_boxes)
+            .with_stage(
+                PhysicsStage::Substeps,
+                Schedule::default()
+                .with_run_criteria(run_substeps)
+                .with_stage(
+                    PhysicsStage::Solve,
+                    SystemStage::parallel()
+                    .with_system(integrate.label(Step::Integrate))
+                    .with_system(integrate_kinematic.label(Step::Integrate))
+                    .with_system(clear_contacts.before(Step::SolvePositions))
+                    .with_system_set(
+                        SystemSet::new()
+                            .label(Step::SolvePositions)
+                            .after(Step::Integrate)
+                            // These all move bodies, so they never run in parallel anyway.
+                            // A fixed order keeps the results the same from run to run.
+                            .with_system(solve_distance_constraints)
+                            .with_system(solve_area_constraints.after(solve_distance_constraints))
+                            .with_system(solve_cohesion.after(solve_area_constraints))
+                            .with_system(solve_pos.after(solve_cohesion))
+                            .with_system(solve_fluid_density.after(solve_pos))
+                            .with_system(solve_pos_statics.after(solve_fluid_density)),
+                    )
+                    .with_system(
+                        update_vel
+                            .label(Step::UpdateVelocities)
+                            .after(Step::SolvePositions),
+                    )
+                    .with_system_set(
+                        SystemSet::new()
+                            .label(Step::SolveVelocities)
+                            .after(Step::UpdateVelocities)
+                            .with_system(solve_vel)
+                            .with_system(solve_fluid_velocities.after(solve_vel))
+                            .with_system(solve_vel_statics.after(solve_fluid_velocities))
+                            .with_system(solve_rolling_resistance.after(solve_vel_statics)),
+                    )
+                    .with_system(collect_step_collisions.after(Step::SolvePositions)),
+                ),
             )
-            .with_system(
-                update_vel
-                    .label(Step::UpdateVelocities)
-                    .after(Step::SolvePositions),
+            .with_stage(
+                PhysicsStage::Sync,
+                SystemStage::parallel()
+                .with_system(sync_transforms)
+                .with_system(hash_state)
+                .with_system(count_recorded_step)
+                .with_system(send_collision_events),
             )
-            .with_system_set(
-                SystemSet::new()
-                    .label(Step::SolveVelocities)
-                    .after(Step::UpdateVelocities)
-                    .with_system(solve_vel)
-                    .with_system(solve_vel_statics),
-            )
-            .with_system(sync_transforms.after(Step::SolveVelocities))
             );
     }
 }
 
+/// Runs the physics steps that fit into the time passed since the last frame,
+/// or the queued ones with `PhysicsStepping::Manual`. Reads `Timestep` every
+/// frame, so it can be changed while the app is running.
+fn run_fixed_timestep(
+    time: Res<Time>,
+    timestep: Res<Timestep>,
+    mut stepping: ResMut<PhysicsStepping>,
+    mut accumulator: ResMut<FixedStepAccumulator>,
+    mut looping: Local<bool>,
+) -> ShouldRun {
+    if timestep.is_changed() {
+        check_timestep(timestep.0);
+    }
+    if let PhysicsStepping::Manual { queued } = stepping.as_mut() {
+        return if *queued > 0 {
+            *queued -= 1;
+            ShouldRun::YesAndCheckAgain
+        } else {
+            ShouldRun::No
+        };
+    }
+
+    // Time only advances on the first check of a frame, not on the repeated ones
+    if !*looping {
+        accumulator.0 += time.delta_seconds_f64();
+    }
+    let step = timestep.0 as f64;
+    if accumulator.0 >= step {
+        accumulator.0 -= step;
+        *looping = true;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        *looping = false;
+        ShouldRun::No
+    }
+}
+
+/// A timestep of zero would never use up the accumulated time and step forever
+fn check_timestep(timestep: f32) {
+    assert!(timestep > 0., "the physics timestep must be positive, got {timestep}");
+}
+
+/// Runs the substep stage `SubstepCount` times for every fixed physics step
+fn run_substeps(substeps: Res<SubstepCount>, mut substep: Local<u32>) -> ShouldRun {
+    if substeps.is_changed() {
+        assert!(substeps.0 > 0, "SubstepCount must be at least 1");
+    }
+    if *substep < substeps.0 {
+        *substep += 1;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        *substep = 0;
+        ShouldRun::No
+    }
+}
+
+/// Colliders a body can have
+type AnyCollider = AnyOf<(
+    &'static CircleCollider,
+    &'static BoxCollider,
+    &'static ConvexPolygonCollider,
+    &'static CapsuleCollider,
+    &'static SegmentCollider,
+    &'static ChainCollider,
+)>;
+type Colliders<'a> = (
+    Option<&'a CircleCollider>,
+    Option<&'a BoxCollider>,
+    Option<&'a ConvexPolygonCollider>,
+    Option<&'a CapsuleCollider>,
+    Option<&'a SegmentCollider>,
+    Option<&'a ChainCollider>,
+);
+type ColliderQuery<'w, 's> = Query<'w, 's, AnyCollider>;
+
+/// Statics whose change invalidates the static BVH. Kinematic bodies move every step, so
+/// they stay out of it.
+type ChangedStatics = (
+    Without<Mass>,
+    Without<Kinematic>,
+    Or<(
+        Changed<Pos>,
+        Changed<Rot>,
+        Changed<CircleCollider>,
+        Changed<BoxCollider>,
+        Changed<ConvexPolygonCollider>,
+        Changed<CapsuleCollider>,
+        Changed<SegmentCollider>,
+        Changed<ChainCollider>,
+    )>,
+);
+
+/// Statics that never move
+type FixedStatics = (Without<Mass>, Without<Kinematic>);
+
+/// Statics moved by user code
+type Kinematics = (With<Kinematic>, Without<Mass>);
+
+/// Everything the position solve reads and writes on a dynamic body. Bodies without
+/// all of the rotation components are solved as if they can't rotate.
+type PosBodyItems = (
+    &'static mut Pos,
+    &'static PrevPos,
+    Option<&'static mut Rot>,
+    Option<&'static PrevRot>,
+    &'static Mass,
+    Option<&'static Inertia>,
+    Option<&'static Friction>,
+);
+
+/// Everything the position solve reads on a static body
+type StaticPosItems = (
+    &'static Pos,
+    Option<&'static Rot>,
+    AnyCollider,
+    Option<&'static Friction>,
+    Option<&'static Vel>,
+    Option<&'static AngVel>,
+);
+
+/// Everything the velocity solve reads on a static body
+type StaticVelItems = (
+    Option<&'static Restitution>,
+    Option<&'static Friction>,
+    Option<&'static Vel>,
+    Option<&'static AngVel>,
+);
+
+/// Everything `hash_state` folds into the hash
+type HashedItems = (
+    Entity,
+    &'static Pos,
+    Option<&'static Rot>,
+    Option<&'static Vel>,
+    Option<&'static AngVel>,
+);
+
+/// Everything the broad phase needs to size a body's proxy
+type BroadPhaseItems = (
+    Entity,
+    &'static Pos,
+    &'static Vel,
+    AnyCollider,
+    Option<&'static Fluid>,
+    Option<&'static Granular>,
+);
+
+/// Everything the velocity solve reads and writes on a dynamic body, with the rotation
+/// components optional like in `PosBodyItems`
+type VelBodyItems = (
+    &'static mut Vel,
+    &'static PreSolveVel,
+    Option<&'static mut AngVel>,
+    Option<&'static PreSolveAngVel>,
+    &'static Mass,
+    Option<&'static Inertia>,
+    Option<&'static Restitution>,
+    Option<&'static Friction>,
+);
+
+/// Radius of a circle around the body's center that contains its whole collider
+fn bounding_radius(colliders: Colliders) -> f32 {
+    match colliders {
+        (Some(circle), ..) => circle.radius,
+        (_, Some(box_collider), ..) => box_collider.size.length() / 2.,
+        (_, _, Some(polygon), ..) => polygon.bounding_radius(),
+        (_, _, _, Some(capsule), ..) => capsule.length / 2. + capsule.radius,
+        (_, _, _, _, Some(segment), _) => segment.a.length().max(segment.b.length()),
+        (_, _, _, _, _, Some(chain)) => chain.vertices.iter().map(|v| v.length()).fold(0., f32::max),
+        (None, None, None, None, None, None) => 0.,
+    }
+}
+
+/// Places a body's collider in the world
+fn world_shape(colliders: Colliders, pos: Vec2, rot: f32) -> WorldShape {
+    match colliders {
+        (Some(circle), ..) => WorldShape::Circle {
+            center: pos,
+            radius: circle.radius,
+        },
+        (_, Some(box_collider), ..) => WorldShape::Polygon(box_collider.vertices(pos, rot)),
+        (_, _, Some(polygon), ..) => WorldShape::Polygon(polygon.world_vertices(pos, rot)),
+        (_, _, _, Some(capsule), ..) => WorldShape::Capsule {
+            ends: capsule.ends(pos, rot),
+            radius: capsule.radius,
+        },
+        (_, _, _, _, Some(segment), _) => WorldShape::Capsule {
+            ends: segment.ends(pos, rot),
+            radius: 0.,
+        },
+        (_, _, _, _, _, Some(chain)) => WorldShape::Chain {
+            vertices: chain.world_vertices(pos, rot),
+            closed: chain.closed,
+        },
+        (None, None, None, None, None, None) => unreachable!("AnyOf always matches at least one collider"),
+    }
+}
+
 fn collect_collision_pairs(
-    query: Query<(Entity, &Pos, &Vel, &CircleCollider)>,
+    query: Query<BroadPhaseItems, With<Mass>>,
+    fluid_settings: Res<FluidSettings>,
+    timestep: Res<Timestep>,
+    deterministic: Res<Deterministic>,
+    mut broad_phase: ResMut<BroadPhaseMethod>,
+    mut proxies: Local<Vec<BroadPhaseProxy>>,
     mut collision_pairs: ResMut<CollisionPairs>,
 ) {
     collision_pairs.0.clear();
 
+    proxies.clear();
+    proxies.extend(query.iter().map(|(entity, pos, vel, colliders, fluid, granular)| {
+        let mut radius = bounding_radius(colliders);
+        // Grow fluid particles so the pairs also hold their neighbours within the kernel
+        if fluid.is_some() {
+            radius = radius.max(fluid_settings.kernel_radius / 2.);
+        }
+        // and grains so they find the ones close enough to stick to
+        if let Some(granular) = granular {
+            radius += granular.cohesion_range / 2.;
+        }
+        BroadPhaseProxy {
+            entity,
+            pos: pos.0,
+            radius,
+            margin: safety_margin(vel.0, timestep.0),
+        }
+    }));
+
+    broad_phase.0.collect_pairs(&proxies, &mut collision_pairs.0);
+    // The pairs come out in whatever order the broad phase finds them in
+    if deterministic.0 {
+        collision_pairs.0.sort_unstable();
+    }
+}
+
+/// How far a body may move during one step before its collision pairs are stale
+fn safety_margin(vel: Vec2, timestep: f32) -> f32 {
     let k = 2.; // safety margin multiplier bigger than 1 to account for sudden accelerations
-    let safety_margin_factor = k * DELTA_TIME;
-    let safety_margin_factor_sqr = safety_margin_factor * safety_margin_factor;
-
-    unsafe {
-        for (entity_a, pos_a, vel_a, circle_a) in query.iter_unsafe() {
-            let vel_a_sqr = vel_a.0.length_squared();
-            for (entity_b, pos_b, vel_b, circle_b) in query.iter_unsafe() {
-                // Ensure safety
-                if entity_a <= entity_b {
-                    continue;
-                }
-
-                let ab = pos_b.0 - pos_a.0;
-                let vel_b_sqr = vel_b.0.length_squared();
-                let safety_margin_sqr = safety_margin_factor_sqr * (vel_a_sqr + vel_b_sqr);
-
-                let combined_radius = circle_a.radius + circle_b.radius + safety_margin_sqr.sqrt();
-
-                let ab_sqr_len = ab.length_squared();
-                if ab_sqr_len < combined_radius * combined_radius {
-                    collision_pairs.0.push((entity_a, entity_b));
-                }
+    k * timestep * vel.length()
+}
+
+fn update_static_bvh(
+    changed: Query<(), ChangedStatics>,
+    statics: Query<(Entity, &Pos, Option<&Rot>, AnyCollider), FixedStatics>,
+    mut bvh: ResMut<StaticBvh>,
+) {
+    // Removed statics don't show up as changes, but they do change the count
+    if changed.is_empty() && statics.iter().len() == bvh.0.len() {
+        return;
+    }
+
+    let leaves = statics
+        .iter()
+        .map(|(entity, pos, rot, colliders)| {
+            let rot = rot.map_or(0., |rot| rot.0);
+            (entity, world_shape(colliders, pos.0, rot).aabb())
+        })
+        .collect();
+    bvh.0 = Bvh::build(leaves);
+}
+
+fn collect_static_collision_pairs(
+    dynamics: Query<(Entity, &Pos, &Vel, AnyCollider), With<Mass>>,
+    kinematics: Query<(Entity, &Pos, &Vel, AnyCollider), Kinematics>,
+    bvh: Res<StaticBvh>,
+    timestep: Res<Timestep>,
+    deterministic: Res<Deterministic>,
+    mut kinematic_aabbs: Local<Vec<(Entity, Aabb)>>,
+    mut static_collision_pairs: ResMut<StaticCollisionPairs>,
+) {
+    kinematic_aabbs.clear();
+    kinematic_aabbs.extend(kinematics.iter().map(|(entity, pos, vel, colliders)| {
+        (entity, Aabb::from_circle(pos.0, bounding_radius(colliders) + safety_margin(vel.0, timestep.0)))
+    }));
+
+    static_collision_pairs.0.clear();
+    for (entity, pos, vel, colliders) in dynamics.iter() {
+        let aabb = Aabb::from_circle(pos.0, bounding_radius(colliders) + safety_margin(vel.0, timestep.0));
+        bvh.0.query(&aabb, |static_entity| {
+            static_collision_pairs.0.push((entity, static_entity));
+        });
+        // There are only ever a few kinematic bodies, so they are tested one by one
+        for (kinematic, kinematic_aabb) in kinematic_aabbs.iter() {
+            if aabb.intersects(kinematic_aabb) {
+                static_collision_pairs.0.push((entity, *kinematic));
             }
         }
     }
+    if deterministic.0 {
+        static_collision_pairs.0.sort_unstable();
+    }
 }
 
-fn integrate( mut query: Query<(&mut Pos, &mut PrevPos, &mut Vel, &mut PreSolveVel, &Mass)>, gravity: Res<Gravity>, ) {
+fn integrate(
+    mut query: Query<(&mut Pos, &mut PrevPos, &mut Vel, &mut PreSolveVel, &Mass)>,
+    mut rotations: Query<(&mut Rot, &mut PrevRot, &AngVel, &mut PreSolveAngVel)>,
+    gravity: Res<Gravity>,
+    substeps: Res<SubstepCount>,
+    timestep: Res<Timestep>,
+) {
+    let sub_dt = substeps.sub_dt(&timestep);
     for (mut pos, mut prev_pos, mut vel, mut pre_solve_vel, mass) in query.iter_mut() {
         prev_pos.0 = pos.0;
 
         let gravitation_force = mass.0 * gravity.0;
         let external_forces = gravitation_force;
-        vel.0 += DELTA_TIME * external_forces / mass.0;
-        pos.0 += DELTA_TIME * vel.0;
+        vel.0 += sub_dt * external_forces / mass.0;
+        pos.0 += sub_dt * vel.0;
         pre_solve_vel.0 = vel.0;
     }
+
+    for (mut rot, mut prev_rot, ang_vel, mut pre_solve_ang_vel) in rotations.iter_mut() {
+        prev_rot.0 = rot.0;
+        rot.0 += sub_dt * ang_vel.0;
+        pre_solve_ang_vel.0 = ang_vel.0;
+    }
+}
+
+/// Moves kinematic bodies with the velocities user code gave them
+fn integrate_kinematic(
+    mut query: Query<(&mut Pos, Option<&mut Rot>, &Vel, &AngVel), Kinematics>,
+    substeps: Res<SubstepCount>,
+    timestep: Res<Timestep>,
+) {
+    let sub_dt = substeps.sub_dt(&timestep);
+    for (mut pos, rot, vel, ang_vel) in query.iter_mut() {
+        pos.0 += sub_dt * vel.0;
+        if let Some(mut rot) = rot {
+            rot.0 += sub_dt * ang_vel.0;
+        }
+    }
+}
+
+/// Positional state of a body in a contact. Static bodies have zero inverse mass.
+struct PosBody<'a> {
+    pos: &'a mut Vec2,
+    prev_pos: Vec2,
+    /// `None` for bodies that can't rotate
+    rot: Option<&'a mut f32>,
+    prev_rot: f32,
+    inv_mass: f32,
+    inv_inertia: f32,
+}
+
+impl<'a> PosBody<'a> {
+    /// Bodies missing any of the rotation components only move, they don't rotate
+    fn dynamic(
+        pos: &'a mut Vec2,
+        prev_pos: &PrevPos,
+        rot: Option<&'a mut Rot>,
+        prev_rot: Option<&PrevRot>,
+        mass: &Mass,
+        inertia: Option<&Inertia>,
+    ) -> Self {
+        let (rot, prev_rot, inv_inertia) = match (rot, prev_rot, inertia) {
+            (Some(rot), Some(prev_rot), Some(inertia)) => (Some(&mut rot.0), prev_rot.0, 1. / inertia.0),
+            _ => (None, 0., 0.),
+        };
+        Self {
+            pos,
+            prev_pos: prev_pos.0,
+            rot,
+            prev_rot,
+            inv_mass: 1. / mass.0,
+            inv_inertia,
+        }
+    }
+
+    /// A body that can't be pushed. Kinematic bodies pass their velocity so static friction
+    /// carries whatever rests on them along.
+    fn fixed(pos: &'a mut Vec2, rot: &'a mut f32, vel: Vec2, ang_vel: f32, sub_dt: f32) -> Self {
+        Self {
+            prev_pos: *pos - vel * sub_dt,
+            prev_rot: *rot - ang_vel * sub_dt,
+            pos,
+            rot: Some(rot),
+            inv_mass: 0.,
+            inv_inertia: 0.,
+        }
+    }
+
+    /// Generalized inverse mass for a correction along `dir` applied at `r` from the center
+    fn inverse_mass(&self, r: Vec2, dir: Vec2) -> f32 {
+        let r_cross_dir = r.perp_dot(dir);
+        self.inv_mass + self.inv_inertia * r_cross_dir * r_cross_dir
+    }
+
+    fn apply_correction(&mut self, p: Vec2, r: Vec2) {
+        *self.pos += p * self.inv_mass;
+        if let Some(rot) = self.rot.as_deref_mut() {
+            *rot += r.perp_dot(p) * self.inv_inertia;
+        }
+    }
+
+    fn angle(&self) -> f32 {
+        self.rot.as_deref().map_or(0., |rot| *rot)
+    }
+
+    /// How far the point at `r` from the center moved during this substep
+    fn displacement(&self, r: Vec2) -> Vec2 {
+        *self.pos - self.prev_pos + (self.angle() - self.prev_rot) * r.perp()
+    }
+}
+
+/// Pushes two bodies apart at every contact point, then applies static friction once the
+/// whole manifold is resolved. Manifolds with several points are iterated so the
+/// corrections balance out instead of favouring whichever point is solved first.
+fn solve_contact_pos(
+    (entity_a, a): (Entity, &mut PosBody),
+    (entity_b, b): (Entity, &mut PosBody),
+    points: &[ContactPoint],
+    static_coeff: f32,
+    sub_dt: f32,
+    contacts: &mut Vec<Contact>,
+) {
+    const MANIFOLD_ITERATIONS: usize = 4;
+
+    let (start_pos_a, start_rot_a) = (*a.pos, a.angle());
+    let (start_pos_b, start_rot_b) = (*b.pos, b.angle());
+    let first_contact = contacts.len();
+    contacts.extend(points.iter().map(|point| Contact {
+        entity_a,
+        entity_b,
+        normal: point.normal,
+        penetration_depth: 0.,
+        normal_impulse: 0.,
+        r_a: point.point_a - start_pos_a,
+        r_b: point.point_b - start_pos_b,
+    }));
+
+    let iterations = if points.len() > 1 { MANIFOLD_ITERATIONS } else { 1 };
+    for _ in 0..iterations {
+        for (point, contact) in points.iter().zip(&mut contacts[first_contact..]) {
+            let (n, r_a, r_b) = (contact.normal, contact.r_a, contact.r_b);
+            let moved_a = *a.pos - start_pos_a + (a.angle() - start_rot_a) * r_a.perp();
+            let moved_b = *b.pos - start_pos_b + (b.angle() - start_rot_b) * r_b.perp();
+            let penetration_depth = point.penetration_depth + Vec2::dot(moved_a - moved_b, n);
+            if penetration_depth <= 0. {
+                continue;
+            }
+
+            let w_sum = a.inverse_mass(r_a, n) + b.inverse_mass(r_b, n);
+            if w_sum <= 0. {
+                continue;
+            }
+            let p = n * penetration_depth / w_sum;
+            a.apply_correction(-p, r_a);
+            b.apply_correction(p, r_b);
+            // Total depth resolved at this point, used as the normal force for friction
+            contact.penetration_depth += penetration_depth;
+            contact.normal_impulse += penetration_depth / w_sum / sub_dt;
+        }
+
+        for contact in &contacts[first_contact..] {
+            let (n, r_a, r_b) = (contact.normal, contact.r_a, contact.r_b);
+            let relative_motion = a.displacement(r_a) - b.displacement(r_b);
+            if let Some(slip) = static_friction(relative_motion, n, contact.penetration_depth, static_coeff) {
+                let t = slip.normalize();
+                let p = -slip / (a.inverse_mass(r_a, t) + b.inverse_mass(r_b, t));
+                a.apply_correction(p, r_a);
+                b.apply_correction(-p, r_b);
+            }
+        }
+    }
+
+    // Only keep the points that actually pushed the bodies apart
+    let mut i = first_contact;
+    while i < contacts.len() {
+        if contacts[i].penetration_depth > 0. {
+            i += 1;
+        } else {
+            contacts.swap_remove(i);
+        }
+    }
 }
 
 fn solve_pos(
-    mut query: Query<(&mut Pos, &CircleCollider, &Mass)>,
+    query: Query<PosBodyItems>,
+    colliders: ColliderQuery,
     collision_pairs: Res<CollisionPairs>,
+    mut contacts: ResMut<Contacts>,
+    substeps: Res<SubstepCount>,
+    timestep: Res<Timestep>,
+    mut points: Local<Vec<ContactPoint>>,
 ) {
-    for (entity_a, entity_b) in collision_pairs.0.iter() {
+    let sub_dt = substeps.sub_dt(&timestep);
+    for (entity_a, entity_b) in collision_pairs.0.iter().cloned() {
+        assert!(entity_a != entity_b); // Ensure we don't violate memory constraints
         let (
-            (mut pos_a, circle_a, mass_a),
-            (mut pos_b, circle_b, mass_b),
-        ) = unsafe {
-            assert!(entity_a != entity_b); // Ensure we don't violate memory constraints
-            (
-                query.get_unchecked(*entity_a).unwrap(),
-                query.get_unchecked(*entity_b).unwrap(),
-            )
+            Ok((mut pos_a, prev_pos_a, mut rot_a, prev_rot_a, mass_a, inertia_a, friction_a)),
+            Ok((mut pos_b, prev_pos_b, mut rot_b, prev_rot_b, mass_b, inertia_b, friction_b)),
+        ) = (unsafe { query.get_unchecked(entity_a) }, unsafe { query.get_unchecked(entity_b) }) else {
+            continue;
         };
-        let ab = pos_b.0 - pos_a.0;
-        let combined_radius = circle_a.radius + circle_b.radius;
-        let ab_sqr_len = ab.length_squared();
-        if ab_sqr_len < combined_radius * combined_radius {
-            let ab_length = ab_sqr_len.sqrt();
-            let penetration_depth = combined_radius - ab_length;
-            let n = ab / ab_length;
-
-            let w_a = 1. / mass_a.0;
-            let w_b = 1. / mass_b.0;
-            let w_sum = w_a + w_b;
-
-            pos_a.0 -= n * penetration_depth * w_a / w_sum;
-            pos_b.0 += n * penetration_depth * w_b / w_sum;
+        let (Ok(collider_a), Ok(collider_b)) = (colliders.get(entity_a), colliders.get(entity_b)) else {
+            continue;
+        };
+
+        points.clear();
+        narrow_phase::contacts(
+            &world_shape(collider_a, pos_a.0, rot_a.as_deref().map_or(0., |rot| rot.0)),
+            &world_shape(collider_b, pos_b.0, rot_b.as_deref().map_or(0., |rot| rot.0)),
+            &mut points,
+        );
+        if points.is_empty() {
+            continue;
         }
+
+        let static_coeff = (friction(friction_a).static_coeff + friction(friction_b).static_coeff) / 2.;
+        solve_contact_pos(
+            (entity_a, &mut PosBody::dynamic(&mut pos_a.0, prev_pos_a, rot_a.as_deref_mut(), prev_rot_a, mass_a, inertia_a)),
+            (entity_b, &mut PosBody::dynamic(&mut pos_b.0, prev_pos_b, rot_b.as_deref_mut(), prev_rot_b, mass_b, inertia_b)),
+            &points,
+            static_coeff,
+            sub_dt,
+            &mut contacts.0,
+        );
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn solve_pos_statics(
-    mut dynamics: Query<(Entity, &mut Pos, &CircleCollider), With<Mass>>, // <-- new
-    statics: Query<(Entity, &Pos, &CircleCollider), Without<Mass>>, 
+    mut dynamics: Query<PosBodyItems>,
+    colliders: ColliderQuery,
+    statics: Query<StaticPosItems, Without<Mass>>,
+    static_collision_pairs: Res<StaticCollisionPairs>,
     mut contacts: ResMut<StaticContacts>,
+    substeps: Res<SubstepCount>,
+    timestep: Res<Timestep>,
+    mut points: Local<Vec<ContactPoint>>,
 ) {
-    for (entity_a, mut pos_a, circle_a) in dynamics.iter_mut() {
-        for (entity_b, pos_b, circle_b) in statics.iter() {
-            let ab = pos_b.0 - pos_a.0;
-            let combined_radius = circle_a.radius + circle_b.radius;
-            let ab_sqr_len = ab.length_squared();
-            if ab_sqr_len < combined_radius * combined_radius {
-                let ab_length = ab_sqr_len.sqrt();
-                let penetration_depth = combined_radius - ab_length;
-                let n = ab / ab_length;
-                pos_a.0 -= n * penetration_depth;
-                contacts.0.push((entity_a, entity_b, n)); // <-- new
+    let sub_dt = substeps.sub_dt(&timestep);
+    for (entity_a, entity_b) in static_collision_pairs.0.iter().cloned() {
+        let Ok((pos_b, rot_b, collider_b, friction_b, vel_b, ang_vel_b)) = statics.get(entity_b) else {
+            continue;
+        };
+        let Ok((mut pos_a, prev_pos_a, mut rot_a, prev_rot_a, mass_a, inertia_a, friction_a)) = dynamics.get_mut(entity_a) else {
+            continue;
+        };
+        let Ok(collider_a) = colliders.get(entity_a) else {
+            continue;
+        };
+        let rot_b = rot_b.map_or(0., |rot| rot.0);
+
+        points.clear();
+        narrow_phase::contacts(
+            &world_shape(collider_a, pos_a.0, rot_a.as_deref().map_or(0., |rot| rot.0)),
+            &world_shape(collider_b, pos_b.0, rot_b),
+            &mut points,
+        );
+        if points.is_empty() {
+            continue;
+        }
+
+        let (mut static_pos, mut static_rot) = (pos_b.0, rot_b);
+        let static_coeff = (friction(friction_a).static_coeff + friction(friction_b).static_coeff) / 2.;
+        solve_contact_pos(
+            (entity_a, &mut PosBody::dynamic(&mut pos_a.0, prev_pos_a, rot_a.as_deref_mut(), prev_rot_a, mass_a, inertia_a)),
+            (entity_b, &mut PosBody::fixed(&mut static_pos, &mut static_rot, kinematic_vel(vel_b), kinematic_ang_vel(ang_vel_b), sub_dt)),
+            &points,
+            static_coeff,
+            sub_dt,
+            &mut contacts.0,
+        );
+    }
+}
+
+/// Friction of a body, the default coefficients for bodies spawned without the component
+fn friction(friction: Option<&Friction>) -> Friction {
+    friction.cloned().unwrap_or_default()
+}
+
+/// Restitution of a body, the default for bodies spawned without the component
+fn restitution_coeff(restitution: Option<&Restitution>) -> f32 {
+    restitution.cloned().unwrap_or_default().0
+}
+
+/// Returns the tangential motion to undo if the contact is held by static friction,
+/// i.e. if it slid less than the friction coefficient times the penetration depth
+fn static_friction(relative_motion: Vec2, n: Vec2, penetration_depth: f32, static_coeff: f32) -> Option<Vec2> {
+    let tangential_motion = relative_motion - n * Vec2::dot(relative_motion, n);
+    let slip = tangential_motion.length();
+    if slip > f32::EPSILON && slip < static_coeff * penetration_depth {
+        Some(tangential_motion)
+    } else {
+        None
+    }
+}
+
+/// Returns the change in relative velocity caused by dynamic friction. The normal force
+/// is derived from the penetration depth resolved during the position solve.
+fn dynamic_friction(relative_vel: Vec2, n: Vec2, penetration_depth: f32, dynamic_coeff: f32, sub_dt: f32) -> Vec2 {
+    let tangential_vel = relative_vel - n * Vec2::dot(relative_vel, n);
+    let tangential_speed = tangential_vel.length();
+    if tangential_speed <= f32::EPSILON {
+        return Vec2::ZERO;
+    }
+    let max_change = dynamic_coeff * penetration_depth / sub_dt;
+    -tangential_vel / tangential_speed * max_change.min(tangential_speed)
+}
+
+/// Velocity state of a body in a contact. Static bodies have zero inverse mass.
+struct VelBody<'a> {
+    vel: &'a mut Vec2,
+    /// `None` for bodies that can't rotate
+    ang_vel: Option<&'a mut f32>,
+    pre_solve_vel: Vec2,
+    pre_solve_ang_vel: f32,
+    inv_mass: f32,
+    inv_inertia: f32,
+}
+
+impl<'a> VelBody<'a> {
+    /// Bodies missing any of the rotation components only move, they don't spin
+    fn dynamic(
+        vel: &'a mut Vec2,
+        pre_solve_vel: &PreSolveVel,
+        ang_vel: Option<&'a mut AngVel>,
+        pre_solve_ang_vel: Option<&PreSolveAngVel>,
+        mass: &Mass,
+        inertia: Option<&Inertia>,
+    ) -> Self {
+        let (ang_vel, pre_solve_ang_vel, inv_inertia) = match (ang_vel, pre_solve_ang_vel, inertia) {
+            (Some(ang_vel), Some(pre_solve_ang_vel), Some(inertia)) => {
+                (Some(&mut ang_vel.0), pre_solve_ang_vel.0, 1. / inertia.0)
             }
+            _ => (None, 0., 0.),
+        };
+        Self {
+            vel,
+            ang_vel,
+            pre_solve_vel: pre_solve_vel.0,
+            pre_solve_ang_vel,
+            inv_mass: 1. / mass.0,
+            inv_inertia,
+        }
+    }
+
+    fn fixed(vel: &'a mut Vec2, ang_vel: &'a mut f32) -> Self {
+        Self {
+            pre_solve_vel: *vel,
+            pre_solve_ang_vel: *ang_vel,
+            vel,
+            ang_vel: Some(ang_vel),
+            inv_mass: 0.,
+            inv_inertia: 0.,
+        }
+    }
+
+    fn point_vel(&self, r: Vec2) -> Vec2 {
+        *self.vel + self.ang_vel.as_deref().map_or(0., |ang_vel| *ang_vel) * r.perp()
+    }
+
+    fn pre_solve_point_vel(&self, r: Vec2) -> Vec2 {
+        self.pre_solve_vel + self.pre_solve_ang_vel * r.perp()
+    }
+
+    fn inverse_mass(&self, r: Vec2, dir: Vec2) -> f32 {
+        let r_cross_dir = r.perp_dot(dir);
+        self.inv_mass + self.inv_inertia * r_cross_dir * r_cross_dir
+    }
+
+    fn apply_impulse(&mut self, p: Vec2, r: Vec2) {
+        *self.vel += p * self.inv_mass;
+        if let Some(ang_vel) = self.ang_vel.as_deref_mut() {
+            *ang_vel += r.perp_dot(p) * self.inv_inertia;
         }
     }
 }
 
-fn solve_pos_static_boxes(
-    mut dynamics: Query<(Entity, &mut Pos, &CircleCollider), With<Mass>>,
-    statics: Query<(Entity, &Pos, &BoxCollider), Without<Mass>>,
-    mut contacts: ResMut<StaticContacts>,
+/// Splits contacts into runs that belong to the same pair of bodies
+fn manifolds(contacts: &[Contact]) -> impl Iterator<Item = &[Contact]> {
+    let mut rest = contacts;
+    std::iter::from_fn(move || {
+        let first = rest.first()?;
+        let len = rest
+            .iter()
+            .position(|c| c.entity_a != first.entity_a || c.entity_b != first.entity_b)
+            .unwrap_or(rest.len());
+        let (manifold, tail) = rest.split_at(len);
+        rest = tail;
+        Some(manifold)
+    })
+}
+
+/// Applies restitution along the contact normals and dynamic friction along the tangents.
+/// Like the position solve, the normal velocities of a manifold are iterated so that one
+/// point doesn't leave the others spinning.
+fn solve_contact_vel(
+    a: &mut VelBody,
+    b: &mut VelBody,
+    manifold: &[Contact],
+    restitution: f32,
+    dynamic_coeff: f32,
+    gravity: Vec2,
+    sub_dt: f32,
 ) {
-    for (entity_a, mut pos_a, circle_a) in dynamics.iter_mut() {
-        for (entity_b, pos_b, box_b) in statics.iter() {
-            let box_to_circle = pos_a.0 - pos_b.0;
-            let box_to_circle_abs = box_to_circle.abs();
-            let half_extents = box_b.size / 2.;
-            let corner_to_center = box_to_circle_abs - half_extents;
-            let r = circle_a.radius;
-            if corner_to_center.x > r || corner_to_center.y > r {
+    const MANIFOLD_ITERATIONS: usize = 4;
+
+    let iterations = if manifold.len() > 1 { MANIFOLD_ITERATIONS } else { 1 };
+    for _ in 0..iterations {
+        for contact in manifold {
+            let n = contact.normal;
+            let (r_a, r_b) = (contact.r_a, contact.r_b);
+
+            let pre_solve_relative_vel = a.pre_solve_point_vel(r_a) - b.pre_solve_point_vel(r_b);
+            let pre_solve_normal_vel = Vec2::dot(pre_solve_relative_vel, n);
+
+            let relative_vel = a.point_vel(r_a) - b.point_vel(r_b);
+            let normal_vel = Vec2::dot(relative_vel, n);
+
+            let w_sum = a.inverse_mass(r_a, n) + b.inverse_mass(r_b, n);
+            if w_sum <= 0. {
                 continue;
             }
-
-            let s = box_to_circle.signum();
-
-            let (n, penetration_depth) = if corner_to_center.x > 0. && corner_to_center.y > 0. {
-                // Corner case
-                let corner_to_center_sqr = corner_to_center.length_squared();
-                if corner_to_center_sqr > r * r {
-                    continue;
-                }
-                let corner_dist = corner_to_center_sqr.sqrt();
-                let penetration_depth = r - corner_dist;
-                let n = corner_to_center / corner_dist * -s;
-                (n, penetration_depth)
-            } else if corner_to_center.x > corner_to_center.y {
-                // Closer to vertical edge
-                (Vec2::X * -s.x, -corner_to_center.x + r)
+            // Resting contacts would otherwise bounce from the velocity gravity adds every substep
+            let restitution = if pre_solve_normal_vel.abs() <= 2. * gravity.length() * sub_dt {
+                0.
             } else {
-                (Vec2::Y * -s.y, -corner_to_center.y + r)
+                restitution
             };
+            let restitution_velocity = (-restitution * pre_solve_normal_vel).min(0.);
+            let vel_impulse = n * ((-normal_vel + restitution_velocity) / w_sum);
+            a.apply_impulse(vel_impulse, r_a);
+            b.apply_impulse(-vel_impulse, r_b);
+        }
+    }
 
-            pos_a.0 -= n * penetration_depth;
-            contacts.0.push((entity_a, entity_b, n));
+    // Friction acts at the contact points, so it also spins the bodies
+    for contact in manifold {
+        let (n, r_a, r_b) = (contact.normal, contact.r_a, contact.r_b);
+        let relative_vel = a.point_vel(r_a) - b.point_vel(r_b);
+        let friction_velocity = dynamic_friction(relative_vel, n, contact.penetration_depth, dynamic_coeff, sub_dt);
+        if friction_velocity != Vec2::ZERO {
+            let t = friction_velocity.normalize();
+            let friction_impulse = friction_velocity / (a.inverse_mass(r_a, t) + b.inverse_mass(r_b, t));
+            a.apply_impulse(friction_impulse, r_a);
+            b.apply_impulse(-friction_impulse, r_b);
         }
     }
 }
 
 fn solve_vel(
-    mut query: Query<(&mut Vel, &PreSolveVel, &Mass, &Restitution)>,
+    query: Query<VelBodyItems>,
     contacts: Res<Contacts>,
+    gravity: Res<Gravity>,
+    substeps: Res<SubstepCount>,
+    timestep: Res<Timestep>,
 ) {
-    for (entity_a, entity_b, n) in contacts.0.iter().cloned() {
+    let sub_dt = substeps.sub_dt(&timestep);
+    for manifold in manifolds(&contacts.0) {
+        let (entity_a, entity_b) = (manifold[0].entity_a, manifold[0].entity_b);
+        assert!(entity_a != entity_b); // Ensure we don't violate memory constraints
+        // The position solve needs fewer components, so not every contact is solved here
         let (
-            (mut vel_a, pre_solve_vel_a, mass_a, restitution_a),
-            (mut vel_b, pre_solve_vel_b, mass_b, restitution_b),
-        ) = unsafe {
-            // Ensure safety
-            assert!(entity_a != entity_b);
-            (
-                query.get_unchecked(entity_a).unwrap(),
-                query.get_unchecked(entity_b).unwrap(),
-            )
+            Ok((mut vel_a, pre_solve_vel_a, mut ang_vel_a, pre_solve_ang_vel_a, mass_a, inertia_a, restitution_a, friction_a)),
+            Ok((mut vel_b, pre_solve_vel_b, mut ang_vel_b, pre_solve_ang_vel_b, mass_b, inertia_b, restitution_b, friction_b)),
+        ) = (unsafe { query.get_unchecked(entity_a) }, unsafe { query.get_unchecked(entity_b) }) else {
+            continue;
         };
-        let pre_solve_relative_vel = pre_solve_vel_a.0 - pre_solve_vel_b.0;
-        let pre_solve_normal_vel = Vec2::dot(pre_solve_relative_vel, n);
+        let restitution = (restitution_coeff(restitution_a) + restitution_coeff(restitution_b)) / 2.;
+        let dynamic_coeff = (friction(friction_a).dynamic_coeff + friction(friction_b).dynamic_coeff) / 2.;
+        solve_contact_vel(
+            &mut VelBody::dynamic(&mut vel_a.0, pre_solve_vel_a, ang_vel_a.as_deref_mut(), pre_solve_ang_vel_a, mass_a, inertia_a),
+            &mut VelBody::dynamic(&mut vel_b.0, pre_solve_vel_b, ang_vel_b.as_deref_mut(), pre_solve_ang_vel_b, mass_b, inertia_b),
+            manifold,
+            restitution,
+            dynamic_coeff,
+            gravity.0,
+            sub_dt,
+        );
+    }
+}
 
-        let relative_vel = vel_a.0 - vel_b.0;
-        let normal_vel = Vec2::dot(relative_vel, n);
-        let restitution = (restitution_a.0 + restitution_b.0) / 2.;
+/// Velocity of a static body, only kinematic ones have one
+fn kinematic_vel(vel: Option<&Vel>) -> Vec2 {
+    vel.map_or(Vec2::ZERO, |vel| vel.0)
+}
 
-        let w_a = 1. / mass_a.0;
-        let w_b = 1. / mass_b.0;
-        let w_sum = w_a + w_b;
+fn kinematic_ang_vel(ang_vel: Option<&AngVel>) -> f32 {
+    ang_vel.map_or(0., |ang_vel| ang_vel.0)
+}
 
-        let restitution_velocity = (-restitution * pre_solve_normal_vel).min(0.);
-        let vel_impulse = n * ((-normal_vel + restitution_velocity) / w_sum);
+fn solve_vel_statics(
+    mut dynamics: Query<VelBodyItems>,
+    statics: Query<StaticVelItems, Without<Mass>>,
+    contacts: Res<StaticContacts>,
+    gravity: Res<Gravity>,
+    substeps: Res<SubstepCount>,
+    timestep: Res<Timestep>,
+) {
+    let sub_dt = substeps.sub_dt(&timestep);
+    for manifold in manifolds(&contacts.0) {
+        let (entity_a, entity_b) = (manifold[0].entity_a, manifold[0].entity_b);
+        let Ok((restitution_b, friction_b, vel_b, ang_vel_b)) = statics.get(entity_b) else {
+            continue;
+        };
+        let Ok((mut vel_a, pre_solve_vel_a, mut ang_vel_a, pre_solve_ang_vel_a, mass_a, inertia_a, restitution_a, friction_a)) =
+            dynamics.get_mut(entity_a)
+        else {
+            continue;
+        };
+        let restitution = (restitution_coeff(restitution_a) + restitution_coeff(restitution_b)) / 2.;
+        let dynamic_coeff = (friction(friction_a).dynamic_coeff + friction(friction_b).dynamic_coeff) / 2.;
+        let (mut static_vel, mut static_ang_vel) = (kinematic_vel(vel_b), kinematic_ang_vel(ang_vel_b));
+        solve_contact_vel(
+            &mut VelBody::dynamic(&mut vel_a.0, pre_solve_vel_a, ang_vel_a.as_deref_mut(), pre_solve_ang_vel_a, mass_a, inertia_a),
+            &mut VelBody::fixed(&mut static_vel, &mut static_ang_vel),
+            manifold,
+            restitution,
+            dynamic_coeff,
+            gravity.0,
+            sub_dt,
+        );
+    }
+}
 
-        vel_a.0 += vel_impulse * w_a;
-        vel_b.0 -= vel_impulse * w_b;
+/// Order to solve constraints in. Queries return them in storage order, which changes as
+/// constraints are despawned and their entities reused, while snapshots and playback
+/// spawn them in entity order. So in deterministic mode they are sorted, like the
+/// collision pairs.
+fn constraint_order(entities: impl Iterator<Item = Entity>, deterministic: &Deterministic, order: &mut Vec<Entity>) {
+    order.clear();
+    order.extend(entities);
+    if deterministic.0 {
+        order.sort_unstable();
     }
 }
 
-fn solve_vel_statics(
-    mut dynamics: Query<(&mut Vel, &PreSolveVel, &Restitution), With<Mass>>,
-    statics: Query<&Restitution, Without<Mass>>,
-    contacts: Res<StaticContacts>,
+fn solve_distance_constraints(
+    mut commands: Commands,
+    constraints: Query<(Entity, &DistanceConstraint, Option<&Tearable>)>,
+    mut bodies: Query<(&mut Pos, Option<&Mass>)>,
+    substeps: Res<SubstepCount>,
+    timestep: Res<Timestep>,
+    deterministic: Res<Deterministic>,
+    mut order: Local<Vec<Entity>>,
+) {
+    let sub_dt = substeps.sub_dt(&timestep);
+    constraint_order(constraints.iter().map(|(entity, ..)| entity), &deterministic, &mut order);
+    for (entity, constraint, tearable) in constraints.iter_many(order.iter()) {
+        let Ok([(mut pos_a, mass_a), (mut pos_b, mass_b)]) = bodies.get_many_mut([constraint.a, constraint.b]) else {
+            continue;
+        };
+        let inv_mass_a = mass_a.map_or(0., |mass| 1. / mass.0);
+        let inv_mass_b = mass_b.map_or(0., |mass| 1. / mass.0);
+        let w_sum = inv_mass_a + inv_mass_b;
+
+        let ab = pos_b.0 - pos_a.0;
+        let length = ab.length();
+        if w_sum <= 0. || length <= f32::EPSILON {
+            continue;
+        }
+        let n = ab / length;
+        let c = length - constraint.rest_length;
+
+        // The Lagrange multiplier starts at zero every substep, so one XPBD iteration is
+        // just the compliant projection
+        let compliance = constraint.compliance / (sub_dt * sub_dt);
+        let delta_lambda = -c / (w_sum + compliance);
+        // Writing to a static anchor would mark it as moved and rebuild the static BVH
+        if inv_mass_a > 0. {
+            pos_a.0 -= n * delta_lambda * inv_mass_a;
+        }
+        if inv_mass_b > 0. {
+            pos_b.0 += n * delta_lambda * inv_mass_b;
+        }
+
+        if let Some(tearable) = tearable {
+            // Stretched links pull with a negative multiplier, squashed ones never tear
+            let tension = -delta_lambda / (sub_dt * sub_dt);
+            if tension > tearable.max_force {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+fn solve_area_constraints(
+    constraints: Query<(Entity, &AreaConstraint)>,
+    mut bodies: Query<(&mut Pos, Option<&Mass>)>,
+    mut ring: Local<Vec<(Vec2, f32)>>,
+    substeps: Res<SubstepCount>,
+    timestep: Res<Timestep>,
+    deterministic: Res<Deterministic>,
+    mut order: Local<Vec<Entity>>,
 ) {
-    for (entity_a, entity_b, n) in contacts.0.iter().cloned() {
-        let (mut vel_a, pre_solve_vel_a, restitution_a) = dynamics.get_mut(entity_a).unwrap();
-        let restitution_b = statics.get(entity_b).unwrap();
-        let pre_solve_normal_vel = Vec2::dot(pre_solve_vel_a.0, n);
-        let normal_vel = Vec2::dot(vel_a.0, n);
-        let restitution = (restitution_a.0 + restitution_b.0) / 2.;
-        vel_a.0 += n * (-normal_vel + (-restitution * pre_solve_normal_vel).min(0.));
+    let sub_dt = substeps.sub_dt(&timestep);
+    constraint_order(constraints.iter().map(|(entity, _)| entity), &deterministic, &mut order);
+    for (_, constraint) in constraints.iter_many(order.iter()) {
+        ring.clear();
+        ring.extend(constraint.ring.iter().filter_map(|entity| {
+            let (pos, mass) = bodies.get(*entity).ok()?;
+            Some((pos.0, mass.map_or(0., |mass| 1. / mass.0)))
+        }));
+        if ring.len() != constraint.ring.len() || ring.len() < 3 {
+            continue;
+        }
+
+        let n = ring.len();
+        let area = 0.5 * (0..n).map(|i| ring[i].0.perp_dot(ring[(i + 1) % n].0)).sum::<f32>();
+        // Moving a point away from the line between its neighbours grows the area the most
+        let gradient = |i: usize| -0.5 * (ring[(i + 1) % n].0 - ring[(i + n - 1) % n].0).perp();
+        let w_sum: f32 = (0..n).map(|i| ring[i].1 * gradient(i).length_squared()).sum();
+
+        let compliance = constraint.compliance / (sub_dt * sub_dt);
+        if w_sum + compliance <= 0. {
+            continue;
+        }
+        let c = area - constraint.rest_area;
+        let delta_lambda = -c / (w_sum + compliance);
+
+        // Like in distance constraints, static points of the ring are left untouched
+        for (i, entity) in constraint.ring.iter().enumerate() {
+            if ring[i].1 <= 0. {
+                continue;
+            }
+            if let Ok((mut pos, _)) = bodies.get_mut(*entity) {
+                pos.0 += gradient(i) * delta_lambda * ring[i].1;
+            }
+        }
     }
 }
 
-fn update_vel(mut query: Query<(&Pos, &PrevPos, &mut Vel)>) {
+fn update_vel(
+    mut query: Query<(&Pos, &PrevPos, &mut Vel)>,
+    mut rotations: Query<(&Rot, &PrevRot, &mut AngVel)>,
+    substeps: Res<SubstepCount>,
+    timestep: Res<Timestep>,
+) {
+    let sub_dt = substeps.sub_dt(&timestep);
     for (pos, prev_pos, mut vel) in query.iter_mut() {
-        vel.0 = (pos.0 - prev_pos.0) / DELTA_TIME;
+        vel.0 = (pos.0 - prev_pos.0) / sub_dt;
+    }
+    for (rot, prev_rot, mut ang_vel) in rotations.iter_mut() {
+        ang_vel.0 = (rot.0 - prev_rot.0) / sub_dt;
     }
 }
 
-/// Copies positions from the physics world to bevy Transforms
-fn sync_transforms(mut query: Query<(&mut bevy::transform::components::Transform, &Pos)>) {
-    for (mut transform, pos) in query.iter_mut() {
+/// Copies positions and rotations from the physics world to bevy Transforms
+fn sync_transforms(
+    mut query: Query<(&mut bevy::transform::components::Transform, &Pos, Option<&Rot>)>,
+    sync: Res<TransformSync>,
+) {
+    if *sync != TransformSync::Immediate {
+        return;
+    }
+    for (mut transform, pos, rot) in query.iter_mut() {
         transform.translation = pos.0.extend(0.);
+        if let Some(rot) = rot {
+            transform.rotation = Quat::from_rotation_z(rot.0);
+        }
+    }
+}
+
+/// Folds the state of every body into `StateHash`, visited in entity order so
+/// the hash doesn't depend on how the bodies are laid out in memory
+fn hash_state(
+    query: Query<HashedItems>,
+    deterministic: Res<Deterministic>,
+    mut bodies: Local<Vec<(Entity, [f32; 6])>>,
+    mut state_hash: ResMut<StateHash>,
+) {
+    if !deterministic.0 {
+        return;
+    }
+    bodies.clear();
+    bodies.extend(query.iter().map(|(entity, pos, rot, vel, ang_vel)| {
+        let vel = vel.map_or(Vec2::ZERO, |vel| vel.0);
+        let state = [
+            pos.0.x,
+            pos.0.y,
+            rot.map_or(0., |rot| rot.0),
+            vel.x,
+            vel.y,
+            ang_vel.map_or(0., |ang_vel| ang_vel.0),
+        ];
+        (entity, state)
+    }));
+    bodies.sort_unstable_by_key(|(entity, _)| *entity);
+
+    // FNV-1a, which unlike the std hashers is the same on every platform and release
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for (entity, state) in bodies.iter() {
+        let bytes = entity.to_bits().to_le_bytes().into_iter();
+        for byte in bytes.chain(state.iter().flat_map(|value| value.to_bits().to_le_bytes())) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100_0000_01b3);
+        }
     }
+    state_hash.step += 1;
+    state_hash.hash = hash;
 }
 
 #[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
 enum Step {
+    UpdateStaticBvh,
     CollectCollisionPairs,
     Integrate,
     SolvePositions,
@@ -0,0 +1,396 @@
+use bevy::prelude::*;
+
+use crate::Aabb;
+
+/// A single point of contact between two shapes `a` and `b`
+#[derive(Debug, Clone, Copy)]
+pub struct ContactPoint {
+    /// Points from `a` towards `b`
+    pub normal: Vec2,
+    pub penetration_depth: f32,
+    /// Deepest point of `a` inside `b`, in world space
+    pub point_a: Vec2,
+    /// Deepest point of `b` inside `a`, in world space
+    pub point_b: Vec2,
+}
+
+impl ContactPoint {
+    /// The same contact seen from the other shape
+    pub fn flipped(self) -> Self {
+        Self {
+            normal: -self.normal,
+            penetration_depth: self.penetration_depth,
+            point_a: self.point_b,
+            point_b: self.point_a,
+        }
+    }
+}
+
+/// A collider placed in the world
+#[derive(Debug, Clone)]
+pub(crate) enum WorldShape {
+    Circle { center: Vec2, radius: f32 },
+    /// Counterclockwise vertices of a convex polygon
+    Polygon(Vec<Vec2>),
+    /// Segment between `ends` grown by `radius`. Line segments have a radius of zero.
+    Capsule { ends: [Vec2; 2], radius: f32 },
+    /// Connected segments, the last vertex joins the first one if `closed`
+    Chain { vertices: Vec<Vec2>, closed: bool },
+}
+
+impl WorldShape {
+    pub(crate) fn aabb(&self) -> Aabb {
+        match self {
+            WorldShape::Circle { center, radius } => Aabb::from_circle(*center, *radius),
+            WorldShape::Polygon(vertices) => Aabb::from_points(vertices.iter().copied()),
+            WorldShape::Capsule { ends, radius } => {
+                let aabb = Aabb::from_points(ends.iter().copied());
+                Aabb {
+                    min: aabb.min - Vec2::splat(*radius),
+                    max: aabb.max + Vec2::splat(*radius),
+                }
+            }
+            WorldShape::Chain { vertices, .. } => Aabb::from_points(vertices.iter().copied()),
+        }
+    }
+
+    /// Core polygon and rounding radius of the shapes that aren't circles
+    fn rounded_polygon(&self) -> Option<(&[Vec2], f32)> {
+        match self {
+            WorldShape::Circle { .. } | WorldShape::Chain { .. } => None,
+            WorldShape::Polygon(vertices) => Some((vertices, 0.)),
+            WorldShape::Capsule { ends, radius } => Some((ends, *radius)),
+        }
+    }
+}
+
+/// Finds the contact points between two shapes and appends them to `points`
+pub(crate) fn contacts(a: &WorldShape, b: &WorldShape, points: &mut Vec<ContactPoint>) {
+    // Polygons with fewer than three vertices, e.g. edited by hand or loaded from a file,
+    // have no edges to separate along and never touch anything
+    if [a, b].iter().any(|shape| matches!(shape, WorldShape::Polygon(vertices) if vertices.len() < 3)) {
+        return;
+    }
+    match (a, b) {
+        (
+            WorldShape::Circle { center: center_a, radius: radius_a },
+            WorldShape::Circle { center: center_b, radius: radius_b },
+        ) => points.extend(circle_circle(*center_a, *radius_a, *center_b, *radius_b)),
+        (WorldShape::Polygon(polygon), WorldShape::Circle { center, radius }) => {
+            points.extend(polygon_circle(polygon, *center, *radius))
+        }
+        (WorldShape::Circle { center, radius }, WorldShape::Polygon(polygon)) => {
+            points.extend(polygon_circle(polygon, *center, *radius).map(ContactPoint::flipped))
+        }
+        (WorldShape::Capsule { ends, radius: capsule_radius }, WorldShape::Circle { center, radius }) => {
+            points.extend(capsule_circle(*ends, *capsule_radius, *center, *radius))
+        }
+        (WorldShape::Circle { center, radius }, WorldShape::Capsule { ends, radius: capsule_radius }) => {
+            points.extend(capsule_circle(*ends, *capsule_radius, *center, *radius).map(ContactPoint::flipped))
+        }
+        // Chains are only used for static terrain, which never collides with itself
+        (WorldShape::Chain { .. }, WorldShape::Chain { .. }) => {}
+        (shape, WorldShape::Chain { vertices, closed }) => shape_chain(shape, vertices, *closed, points),
+        (WorldShape::Chain { vertices, closed }, shape) => {
+            let first = points.len();
+            shape_chain(shape, vertices, *closed, points);
+            for point in &mut points[first..] {
+                *point = point.flipped();
+            }
+        }
+        _ => {
+            let ((polygon_a, radius_a), (polygon_b, radius_b)) = a.rounded_polygon().zip(b.rounded_polygon()).unwrap();
+            rounded_polygon_polygon(polygon_a, radius_a, polygon_b, radius_b, points);
+        }
+    }
+}
+
+pub fn circle_circle(center_a: Vec2, radius_a: f32, center_b: Vec2, radius_b: f32) -> Option<ContactPoint> {
+    let ab = center_b - center_a;
+    let combined_radius = radius_a + radius_b;
+    let ab_sqr_len = ab.length_squared();
+    if ab_sqr_len >= combined_radius * combined_radius {
+        return None;
+    }
+    let ab_length = ab_sqr_len.sqrt();
+    let normal = if ab_length > f32::EPSILON { ab / ab_length } else { Vec2::Y };
+    Some(ContactPoint {
+        normal,
+        penetration_depth: combined_radius - ab_length,
+        point_a: center_a + normal * radius_a,
+        point_b: center_b - normal * radius_b,
+    })
+}
+
+/// Closest point to `p` on the segment from `a` to `b`
+pub fn closest_point_on_segment(p: Vec2, a: Vec2, b: Vec2) -> Vec2 {
+    let ab = b - a;
+    let length_sqr = ab.length_squared();
+    if length_sqr <= f32::EPSILON {
+        return a;
+    }
+    let t = (Vec2::dot(p - a, ab) / length_sqr).clamp(0., 1.);
+    a + ab * t
+}
+
+/// Fraction of a segment's length around its ends in which a polygon contact counts as
+/// being at the joint
+const JOINT_TOLERANCE: f32 = 1e-4;
+
+/// Contacts between a shape `a` and a chain of segments `b`. Where two segments meet, the
+/// joint is only handled by one of them, so shapes slide over it instead of snagging on
+/// the end of the next segment.
+fn shape_chain(a: &WorldShape, vertices: &[Vec2], closed: bool, points: &mut Vec<ContactPoint>) {
+    let vertex_count = vertices.len();
+    let segment_count = match (closed, vertex_count) {
+        (_, 0 | 1) => 0,
+        (true, _) => vertex_count,
+        (false, _) => vertex_count - 1,
+    };
+
+    for i in 0..segment_count {
+        let start = vertices[i];
+        let end = vertices[(i + 1) % vertex_count];
+        let previous = (closed || i > 0).then(|| vertices[(i + vertex_count - 1) % vertex_count]);
+        let has_next = closed || i + 1 < segment_count;
+
+        match a {
+            WorldShape::Circle { center, radius } => {
+                let Some(contact) = capsule_circle([start, end], 0., *center, *radius) else {
+                    continue;
+                };
+                let along = Vec2::dot(*center - start, end - start);
+                // The next segment takes care of the joint at its start
+                if along >= (end - start).length_squared() && has_next {
+                    continue;
+                }
+                // Only take the joint at the start if the circle is past the end of the
+                // previous segment, otherwise that segment already pushes it out
+                if let Some(previous) = previous {
+                    if along <= 0. && Vec2::dot(*center - start, start - previous) < 0. {
+                        continue;
+                    }
+                }
+                points.push(contact.flipped());
+            }
+            _ => {
+                let Some((polygon, radius)) = a.rounded_polygon() else {
+                    continue;
+                };
+                // The same joint rule as for circles, with the middle of the polygon in place of
+                // the circle's center. Both segments at a joint see the same middle, so exactly one
+                // of them takes the contacts there, whatever normal each of them found.
+                let middle = polygon.iter().sum::<Vec2>() / polygon.len() as f32;
+                let length_sqr = (end - start).length_squared();
+                let first = points.len();
+                rounded_polygon_polygon(polygon, radius, &[start, end], 0., points);
+                let mut i = first;
+                while i < points.len() {
+                    let along = Vec2::dot(points[i].point_b - start, end - start);
+                    let next_takes_joint =
+                        has_next && along >= length_sqr * (1. - JOINT_TOLERANCE) && Vec2::dot(middle - end, end - start) >= 0.;
+                    let previous_takes_joint = previous.is_some_and(|previous| {
+                        along <= length_sqr * JOINT_TOLERANCE && Vec2::dot(middle - start, start - previous) < 0.
+                    });
+                    if next_takes_joint || previous_takes_joint {
+                        points.swap_remove(i);
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Outward normal of the edge starting at vertex `i` of a counterclockwise polygon
+fn edge_normal(polygon: &[Vec2], i: usize) -> Vec2 {
+    let edge = polygon[(i + 1) % polygon.len()] - polygon[i];
+    -edge.perp().normalize()
+}
+
+/// Contact between a capsule `a` and a circle `b`, a segment if `capsule_radius` is zero
+pub fn capsule_circle(ends: [Vec2; 2], capsule_radius: f32, center: Vec2, radius: f32) -> Option<ContactPoint> {
+    let closest = closest_point_on_segment(center, ends[0], ends[1]);
+    circle_circle(closest, capsule_radius, center, radius)
+}
+
+/// Contact between a convex polygon `a` and a circle `b`
+pub fn polygon_circle(polygon: &[Vec2], center: Vec2, radius: f32) -> Option<ContactPoint> {
+    // Edge the circle center is furthest in front of
+    let (edge, separation) = (0..polygon.len())
+        .map(|i| (i, Vec2::dot(center - polygon[i], edge_normal(polygon, i))))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+    if separation > radius {
+        return None;
+    }
+
+    if separation <= 0. {
+        // Center inside the polygon, push out through the closest edge
+        let normal = edge_normal(polygon, edge);
+        return Some(ContactPoint {
+            normal,
+            penetration_depth: radius - separation,
+            point_a: center - normal * separation,
+            point_b: center - normal * radius,
+        });
+    }
+
+    let closest = closest_point_on_segment(center, polygon[edge], polygon[(edge + 1) % polygon.len()]);
+    let to_center = center - closest;
+    let distance_sqr = to_center.length_squared();
+    if distance_sqr > radius * radius {
+        return None;
+    }
+    let distance = distance_sqr.sqrt();
+    let normal = if distance > f32::EPSILON {
+        to_center / distance
+    } else {
+        edge_normal(polygon, edge)
+    };
+    Some(ContactPoint {
+        normal,
+        penetration_depth: radius - distance,
+        point_a: closest,
+        point_b: center - normal * radius,
+    })
+}
+
+/// Largest separation of `b` along the edge normals of `a`, and the edge it was found on
+fn max_separation(a: &[Vec2], b: &[Vec2]) -> (usize, f32) {
+    (0..a.len())
+        .map(|i| {
+            let normal = edge_normal(a, i);
+            let separation = b
+                .iter()
+                .map(|v| Vec2::dot(*v - a[i], normal))
+                .fold(f32::INFINITY, f32::min);
+            (i, separation)
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap()
+}
+
+/// Closest points of two convex polygons that don't overlap, and whether both are vertices.
+/// A vertex facing an edge wins ties, so parallel edges aren't mistaken for corners.
+fn closest_points(a: &[Vec2], b: &[Vec2]) -> (Vec2, Vec2, bool) {
+    const CORNER_BIAS: f32 = 1e-6;
+    let mut closest = (a[0], b[0], true);
+    let mut score = f32::INFINITY;
+    for (from, to, flip) in [(a, b, false), (b, a, true)] {
+        for &vertex in from {
+            for i in 0..to.len() {
+                let start = to[i];
+                let edge = to[(i + 1) % to.len()] - start;
+                let t = (Vec2::dot(vertex - start, edge) / edge.length_squared()).clamp(0., 1.);
+                let point = start + edge * t;
+                let corners = t == 0. || t == 1.;
+                let candidate = vertex.distance_squared(point) + if corners { CORNER_BIAS } else { 0. };
+                if candidate < score {
+                    score = candidate;
+                    closest = if flip { (point, vertex, corners) } else { (vertex, point, corners) };
+                }
+            }
+        }
+    }
+    closest
+}
+
+/// Points of a polygon manifold closer than this are kept even if they don't touch yet.
+/// Without them a resting box only ever touches with its deepest corner and starts rocking.
+const MANIFOLD_MARGIN: f32 = 0.01;
+
+/// Contact between two convex polygons grown by a radius, using the separating axis test.
+/// A two vertex polygon is a segment, which makes this the capsule test as well. The
+/// contact points come from clipping the incident edge against the reference edge. Points
+/// within `MANIFOLD_MARGIN` are included with a negative penetration depth. The contacts
+/// are appended to `points`.
+pub fn rounded_polygon_polygon(a: &[Vec2], radius_a: f32, b: &[Vec2], radius_b: f32, points: &mut Vec<ContactPoint>) {
+    let combined_radius = radius_a + radius_b;
+    let (edge_a, separation_a) = max_separation(a, b);
+    if separation_a > combined_radius {
+        return;
+    }
+    let (edge_b, separation_b) = max_separation(b, a);
+    if separation_b > combined_radius {
+        return;
+    }
+
+    // Apart cores of rounded shapes can also meet corner to corner, along the line between
+    // their closest points rather than an edge normal
+    if separation_a.max(separation_b) > 0. {
+        let (closest_a, closest_b, corners) = closest_points(a, b);
+        let distance = closest_a.distance(closest_b);
+        if distance > combined_radius {
+            return;
+        }
+        if corners {
+            let normal = (closest_b - closest_a) / distance;
+            points.push(ContactPoint {
+                normal,
+                penetration_depth: combined_radius - distance,
+                point_a: closest_a + normal * radius_a,
+                point_b: closest_b - normal * radius_b,
+            });
+            return;
+        }
+    }
+
+    // Prefer `a` as the reference polygon to avoid flip-flopping between similar axes
+    let flip = separation_b > separation_a + 1e-3;
+    let (reference, incident, reference_edge) = if flip { (b, a, edge_b) } else { (a, b, edge_a) };
+    let incident_radius = if flip { radius_a } else { radius_b };
+
+    let normal = edge_normal(reference, reference_edge);
+    let v1 = reference[reference_edge];
+    let v2 = reference[(reference_edge + 1) % reference.len()];
+
+    // Incident edge is the one most opposed to the reference normal
+    let incident_edge = (0..incident.len())
+        .min_by(|&i, &j| {
+            Vec2::dot(edge_normal(incident, i), normal).total_cmp(&Vec2::dot(edge_normal(incident, j), normal))
+        })
+        .unwrap();
+    let mut clipped = [incident[incident_edge], incident[(incident_edge + 1) % incident.len()]];
+
+    // Clip the incident edge to the side planes of the reference edge
+    let tangent = (v2 - v1).normalize();
+    for (plane_normal, offset) in [(-tangent, -Vec2::dot(tangent, v1)), (tangent, Vec2::dot(tangent, v2))] {
+        let Some(segment) = clip_segment(clipped, plane_normal, offset) else {
+            return;
+        };
+        clipped = segment;
+    }
+
+    points.extend(clipped.into_iter().filter_map(|point| {
+        let separation = Vec2::dot(point - v1, normal) - combined_radius;
+        if separation > MANIFOLD_MARGIN {
+            return None;
+        }
+        // Both points are on the rounded surfaces
+        let contact = ContactPoint {
+            normal,
+            penetration_depth: -separation,
+            point_a: point - normal * (separation + incident_radius),
+            point_b: point - normal * incident_radius,
+        };
+        Some(if flip { contact.flipped() } else { contact })
+    }));
+}
+
+/// Keeps the part of a segment with `dot(plane_normal, p) <= offset`, `None` if it is
+/// clipped away to less than a segment
+fn clip_segment(points: [Vec2; 2], plane_normal: Vec2, offset: f32) -> Option<[Vec2; 2]> {
+    let distance_0 = Vec2::dot(plane_normal, points[0]) - offset;
+    let distance_1 = Vec2::dot(plane_normal, points[1]) - offset;
+
+    if distance_0 <= 0. && distance_1 <= 0. {
+        return Some(points);
+    }
+    if distance_0 * distance_1 >= 0. {
+        return None;
+    }
+    let t = distance_0 / (distance_0 - distance_1);
+    let crossing = points[0] + (points[1] - points[0]) * t;
+    Some(if distance_0 < 0. { [points[0], crossing] } else { [points[1], crossing] })
+}
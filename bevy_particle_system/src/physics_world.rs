@@ -0,0 +1,67 @@
+use bevy::{ecs::world::EntityMut, prelude::*};
+
+use crate::*;
+
+/// A headless app that only runs the physics and advances it one fixed step
+/// at a time, independent of the wall clock. Meant for tests and batch runs.
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use bevy_particle_system::*;
+///
+/// let mut world = PhysicsWorld::default();
+/// let ball = world.spawn(ParticleBundle::default()).id();
+/// world.steps(60);
+/// let pos = world.get::<Pos>(ball).unwrap();
+/// ```
+pub struct PhysicsWorld {
+    pub app: App,
+}
+
+impl Default for PhysicsWorld {
+    fn default() -> Self {
+        Self::new(XPBDPlugin::default())
+    }
+}
+
+impl PhysicsWorld {
+    pub fn new(plugin: XPBDPlugin) -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(PhysicsStepping::Manual { queued: 0 })
+            .add_plugin(plugin);
+        Self { app }
+    }
+
+    /// Advances the simulation by exactly one `Timestep`
+    pub fn step(&mut self) {
+        self.steps(1);
+    }
+
+    /// Advances the simulation by `n` fixed steps in a single app update
+    pub fn steps(&mut self, n: u32) {
+        self.app.world.resource_mut::<PhysicsStepping>().queue(n);
+        self.app.update();
+    }
+
+    pub fn spawn(&mut self, bundle: impl Bundle) -> EntityMut<'_> {
+        self.app.world.spawn(bundle)
+    }
+
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<&T> {
+        self.app.world.get::<T>(entity)
+    }
+
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<Mut<'_, T>> {
+        self.app.world.get_mut::<T>(entity)
+    }
+
+    pub fn insert_resource(&mut self, resource: impl Resource) -> &mut Self {
+        self.app.insert_resource(resource);
+        self
+    }
+
+    pub fn resource<R: Resource>(&self) -> &R {
+        self.app.world.resource::<R>()
+    }
+}
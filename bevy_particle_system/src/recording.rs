@@ -0,0 +1,191 @@
+use bevy::{prelude::*, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// Everything needed to play a session back: the state it started from and every
+/// body spawned or despawned after that, tagged with the physics step it happened
+/// before. Spawns keep the body's full state, so playback doesn't depend on where
+/// the random numbers that picked it came from. Changes user code makes to bodies that already exist,
+/// like steering a kinematic body, aren't recorded, and neither are constraints
+/// spawned after the start.
+///
+/// Bodies keep their entities during playback, so the collision pairs are sorted the
+/// same way and entities from the session can be looked up in the played back world.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub timestep: f32,
+    pub substeps: u32,
+    /// The world when recording started
+    pub initial: Snapshot,
+    /// Entities of the bodies and constraints in `initial`, see `Snapshot::capture_with_entities`
+    pub initial_entities: Vec<Entity>,
+    /// Number of physics steps recorded
+    pub steps: u64,
+    /// Spawns and despawns in the order they happened
+    pub commands: Vec<RecordedCommand>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RecordedCommand {
+    /// A body appeared after `step` physics steps
+    Spawn { step: u64, entity: Entity, body: Box<BodySnapshot> },
+    /// The body was despawned after `step` physics steps
+    Despawn { step: u64, entity: Entity },
+}
+
+impl RecordedCommand {
+    pub fn step(&self) -> u64 {
+        match self {
+            Self::Spawn { step, .. } | Self::Despawn { step, .. } => *step,
+        }
+    }
+}
+
+impl RonFile for Recording {}
+
+/// Records the spawns and despawns of bodies while it exists as a resource. Run the
+/// plugin in deterministic mode to get the same trajectories back from `Playback`.
+#[derive(Debug, Resource)]
+pub struct PhysicsRecorder {
+    recording: Recording,
+    bodies: HashSet<Entity>,
+}
+
+impl PhysicsRecorder {
+    /// Starts recording from the current state of `world`
+    pub fn start(world: &mut World) {
+        let (initial, initial_entities) = Snapshot::capture_with_entities(world);
+        let bodies = initial_entities[..initial.bodies.len()].iter().copied().collect();
+        let recording = Recording {
+            timestep: world.get_resource::<Timestep>().map_or(DELTA_TIME, |timestep| timestep.0),
+            substeps: world.get_resource::<SubstepCount>().map_or(SubstepCount::default().0, |substeps| substeps.0),
+            initial,
+            initial_entities,
+            steps: 0,
+            commands: Vec::new(),
+        };
+        world.insert_resource(Self { recording, bodies });
+    }
+
+    /// Stops recording and returns what was recorded, if anything was
+    pub fn stop(world: &mut World) -> Option<Recording> {
+        world.remove_resource::<Self>().map(|recorder| recorder.recording)
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+}
+
+/// Runs every frame in `CoreStage::PostUpdate`, once the bodies spawned and despawned by
+/// user systems in `Update` are in the world, and before the next frame's physics steps
+/// see them
+pub(crate) fn record_commands(
+    recorder: Option<ResMut<PhysicsRecorder>>,
+    added: Query<(BodyItems, ShapeItems), Added<Pos>>,
+    removed: RemovedComponents<Pos>,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+    let recorder = &mut *recorder;
+    let step = recorder.recording.steps;
+
+    // Despawns go first, a body spawned in the same frame may reuse a despawned entity
+    let mut despawned: Vec<_> = removed.iter().filter(|entity| recorder.bodies.remove(entity)).collect();
+    despawned.sort_unstable();
+    for entity in despawned {
+        recorder.recording.commands.push(RecordedCommand::Despawn { step, entity });
+    }
+
+    // Sorted so bodies spawned in the same frame are played back in the same order
+    let mut spawned: Vec<_> = added
+        .iter()
+        .filter(|(body, _)| !recorder.bodies.contains(&body.0))
+        .map(|(body, shape)| (body.0, BodySnapshot::from_items(body, shape)))
+        .collect();
+    spawned.sort_unstable_by_key(|(entity, _)| *entity);
+    for (entity, body) in spawned {
+        recorder.bodies.insert(entity);
+        recorder.recording.commands.push(RecordedCommand::Spawn {
+            step,
+            entity,
+            body: Box::new(body),
+        });
+    }
+}
+
+pub(crate) fn count_recorded_step(recorder: Option<ResMut<PhysicsRecorder>>) {
+    if let Some(mut recorder) = recorder {
+        recorder.recording.steps += 1;
+    }
+}
+
+/// Plays a `Recording` back on a headless `PhysicsWorld` in deterministic mode
+pub struct Playback {
+    pub world: PhysicsWorld,
+    recording: Recording,
+    step: u64,
+    next_command: usize,
+}
+
+impl Playback {
+    pub fn new(recording: Recording) -> Self {
+        let mut world = PhysicsWorld::new(XPBDPlugin {
+            timestep: recording.timestep,
+            deterministic: true,
+        });
+        world.insert_resource(SubstepCount(recording.substeps));
+        recording.initial.restore_as(&mut world.app.world, &recording.initial_entities);
+        Self {
+            world,
+            recording,
+            step: 0,
+            next_command: 0,
+        }
+    }
+
+    /// Applies the commands recorded before the next step and runs it.
+    /// Returns false once every recorded step has been played.
+    pub fn step(&mut self) -> bool {
+        if self.finished() {
+            return false;
+        }
+        self.apply_commands();
+        self.world.step();
+        self.step += 1;
+        // Bodies spawned and despawned after the last step still belong to the session
+        if self.finished() {
+            self.apply_commands();
+        }
+        true
+    }
+
+    fn apply_commands(&mut self) {
+        while let Some(command) = self.recording.commands.get(self.next_command) {
+            if command.step() > self.step {
+                break;
+            }
+            match command {
+                RecordedCommand::Spawn { entity, body, .. } => {
+                    let mut entity = self.world.app.world.get_or_spawn(*entity).expect("entity is already in use");
+                    insert_body(&mut entity, body);
+                }
+                RecordedCommand::Despawn { entity, .. } => {
+                    self.world.app.world.despawn(*entity);
+                }
+            }
+            self.next_command += 1;
+        }
+    }
+
+    /// Plays all remaining steps
+    pub fn run(&mut self) {
+        while self.step() {}
+    }
+
+    pub fn finished(&self) -> bool {
+        self.step >= self.recording.steps
+    }
+}
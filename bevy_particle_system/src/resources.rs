@@ -1,4 +1,7 @@
 use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{Bvh, DELTA_TIME};
 
 #[derive(Debug, Resource)]
 pub struct Gravity(pub Vec2);
@@ -9,12 +12,180 @@ impl Default for Gravity {
     }
 }
 
+/// Length of one fixed physics step in seconds, set through `XPBDPlugin::timestep`.
+/// Smaller steps are more accurate but run the whole pipeline more often. Must be
+/// positive, the physics panics on the next frame otherwise.
+#[derive(Debug, Resource)]
+pub struct Timestep(pub f32);
+
+impl Default for Timestep {
+    fn default() -> Self {
+        Self(DELTA_TIME)
+    }
+}
+
+/// Deterministic mode, set through `XPBDPlugin::deterministic`. Collision pairs and
+/// constraints are sorted before solving and `StateHash` is updated after every step,
+/// so two runs with the same spawn sequence can be compared step by step. Combine it
+/// with `PhysicsStepping::Manual` and `PhysicsRng` for replays.
+#[derive(Debug, Default, Resource)]
+pub struct Deterministic(pub bool);
+
+/// Hash of the position and velocity of every body after the latest physics step,
+/// only updated in `Deterministic` mode
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub struct StateHash {
+    /// Number of steps hashed so far
+    pub step: u64,
+    pub hash: u64,
+}
+
+/// Seeded random numbers for spawning and jittering bodies, so the spawn sequence
+/// can be repeated exactly. Seeded with 0 unless inserted before the plugin.
+#[derive(Debug, Resource)]
+pub struct PhysicsRng(pub StdRng);
+
+impl PhysicsRng {
+    pub fn new(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Default for PhysicsRng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Decides when the fixed physics steps run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum PhysicsStepping {
+    /// As many steps as fit into the time that passed since the last frame
+    #[default]
+    RealTime,
+    /// Only the steps queued with `queue`, all of them on the next update no
+    /// matter how much time passed. Used by `PhysicsWorld`.
+    Manual { queued: u32 },
+}
+
+impl PhysicsStepping {
+    /// Queues `steps` more physics steps, switching to manual stepping if needed
+    pub fn queue(&mut self, steps: u32) {
+        match self {
+            Self::Manual { queued } => *queued += steps,
+            Self::RealTime => *self = Self::Manual { queued: steps },
+        }
+    }
+}
+
+/// Time that has passed but not been simulated yet because it is shorter than a `Timestep`
+#[derive(Debug, Default, Resource)]
+pub struct FixedStepAccumulator(pub(crate) f64);
+
+impl FixedStepAccumulator {
+    /// How far the current frame is into the next physics step, from 0 to 1
+    pub fn overstep(&self, timestep: &Timestep) -> f32 {
+        (self.0 / timestep.0 as f64) as f32
+    }
+}
+
+/// How body positions are written to their `Transform`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum TransformSync {
+    /// Copy `Pos` and `Rot` after every physics step. Motion stutters when
+    /// the frame rate doesn't match the timestep.
+    #[default]
+    Immediate,
+    /// Blend between the last two physics steps every frame by the overstep of
+    /// the `FixedStepAccumulator`. Smooth at any frame rate, but shows bodies
+    /// up to one step behind the simulation.
+    Interpolated,
+}
+
+/// Number of solver substeps per fixed physics step. Collision pairs are
+/// collected once per step, while integration and solving run `n` times
+/// with a timestep of `Timestep / n`. Must be at least 1.
+#[derive(Debug, Resource)]
+pub struct SubstepCount(pub u32);
+
+impl Default for SubstepCount {
+    fn default() -> Self {
+        Self(8)
+    }
+}
+
+impl SubstepCount {
+    pub fn sub_dt(&self, timestep: &Timestep) -> f32 {
+        timestep.0 / self.0 as f32
+    }
+}
+
 #[derive(Debug, Default, Resource)]
 pub(crate) struct CollisionPairs(pub Vec<(Entity, Entity)>);
 
-#[derive(Default, Debug, Resource)]
-pub struct Contacts(pub Vec<(Entity, Entity, Vec2)>);
+/// Bounding volume hierarchy over all static colliders, rebuilt whenever a static
+/// body is added, moved or removed
+#[derive(Debug, Default, Resource)]
+pub struct StaticBvh(pub Bvh);
 
+/// Dynamic and static bodies that may collide during this step as `(dynamic, static)`
+#[derive(Debug, Default, Resource)]
+pub(crate) struct StaticCollisionPairs(pub Vec<(Entity, Entity)>);
+
+/// A contact point resolved during the position solve
+#[derive(Debug, Clone, Copy)]
+pub struct Contact {
+    pub entity_a: Entity,
+    pub entity_b: Entity,
+    /// Points from `entity_a` towards `entity_b`
+    pub normal: Vec2,
+    pub penetration_depth: f32,
+    /// Impulse along the normal that pushed the bodies apart at this point
+    pub normal_impulse: f32,
+    /// Contact point on `entity_a` relative to its center
+    pub r_a: Vec2,
+    /// Contact point on `entity_b` relative to its center
+    pub r_b: Vec2,
+}
 
+/// Contacts between two dynamic bodies
 #[derive(Default, Debug, Resource)]
-pub struct StaticContacts(pub Vec<(Entity, Entity, Vec2)>);
\ No newline at end of file
+pub struct Contacts(pub Vec<Contact>);
+
+/// Contacts between a dynamic body (`entity_a`) and a static one (`entity_b`)
+#[derive(Default, Debug, Resource)]
+pub struct StaticContacts(pub Vec<Contact>);
+
+/// Seconds two bodies have to stay apart before their collision ends. Zero by default,
+/// so a collision ends after the first step the bodies don't touch. With a delay, bodies
+/// that touch again sooner, like a box rocking onto its face or a ball settling after a
+/// small hop, keep their collision going instead of starting a new one.
+#[derive(Debug, Default, Resource)]
+pub struct CollisionEndDelay(pub f32);
+
+/// Parameters of the Position Based Fluids solver shared by every `Fluid` particle
+#[derive(Debug, Resource)]
+pub struct FluidSettings {
+    /// Fluid particles closer than this are neighbours, usually about four particle radii
+    pub kernel_radius: f32,
+    /// Mass per area the fluid settles at. 1 kg particles spaced 0.2 apart give 25.
+    pub rest_density: f32,
+    /// Softens the density constraint so it doesn't blow up with few neighbours
+    pub relaxation: f32,
+    /// How strongly neighbours drag each other's velocity along, from 0 to 1
+    pub viscosity: f32,
+    /// Strength of the vorticity confinement that keeps the fluid swirling
+    pub vorticity: f32,
+}
+
+impl Default for FluidSettings {
+    fn default() -> Self {
+        Self {
+            kernel_radius: 0.4,
+            rest_density: 25.,
+            relaxation: 1.,
+            viscosity: 0.01,
+            vorticity: 0.1,
+        }
+    }
+}
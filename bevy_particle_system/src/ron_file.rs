@@ -0,0 +1,26 @@
+use std::{fs, io, path::Path};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Saving to and loading from RON files, for `Snapshot` and `Recording`
+pub trait RonFile: Serialize + DeserializeOwned {
+    fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    fn from_ron(ron: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(ron)
+    }
+
+    /// Writes `self` to a RON file
+    fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let ron = self.to_ron().map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, ron)
+    }
+
+    /// Reads a file written by `save`
+    fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let ron = fs::read_to_string(path)?;
+        Self::from_ron(&ron).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+
+use crate::{
+    builder::{polyline, spawn_particle},
+    *,
+};
+
+/// A rope spawned by `RopeBuilder`. Lives on its own entity.
+#[derive(Component, Debug, Clone)]
+pub struct Rope {
+    /// Particles, pinned anchors and attached entities from the start of the rope to its end
+    pub points: Vec<Entity>,
+}
+
+impl Rope {
+    /// Current positions along the rope, ready to be drawn as a line strip
+    pub fn polyline(&self, positions: &Query<&Pos>) -> Vec<Vec2> {
+        polyline(&self.points, positions)
+    }
+}
+
+/// How one end of a rope is held
+#[derive(Debug, Clone, Copy)]
+enum RopeEnd {
+    Free,
+    /// Held in place by a static anchor spawned with the rope
+    Pinned,
+    /// Tied to an existing entity, which becomes the end point of the rope
+    Attached(Entity),
+}
+
+/// Spawns a rope of particles from `start` to `end`, with neighbours linked by distance
+/// constraints. Pinned ends are static anchors that the rope hangs from, attached ends are
+/// tied to bodies that already exist.
+#[derive(Debug, Clone)]
+pub struct RopeBuilder {
+    start: Vec2,
+    end: Vec2,
+    points: usize,
+    length: Option<f32>,
+    radius: Option<f32>,
+    mass: f32,
+    compliance: f32,
+    start_anchor: RopeEnd,
+    end_anchor: RopeEnd,
+}
+
+impl RopeBuilder {
+    pub fn new(start: Vec2, end: Vec2) -> Self {
+        Self {
+            start,
+            end,
+            points: 10,
+            length: None,
+            radius: None,
+            mass: Mass::default().0,
+            compliance: 0.,
+            start_anchor: RopeEnd::Free,
+            end_anchor: RopeEnd::Free,
+        }
+    }
+
+    /// Number of points along the rope, pinned and attached ends included
+    pub fn points(mut self, points: usize) -> Self {
+        assert!(points >= 2, "a rope needs at least two points");
+        self.points = points;
+        self
+    }
+
+    /// Length of the rope when it is relaxed, defaults to the distance from start to end.
+    /// A longer rope sags.
+    pub fn length(mut self, length: f32) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// Radius of the particles along the rope. Defaults to 0.45 of the link length, which
+    /// leaves the rope smooth to roll along without neighbours pushing each other apart.
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = Some(radius);
+        self
+    }
+
+    /// Mass of the particles along the rope. Attached ends keep their own mass, pinned ones
+    /// have none.
+    pub fn mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    /// Inverse stiffness of the links, zero keeps the rope from stretching at all
+    pub fn compliance(mut self, compliance: f32) -> Self {
+        self.compliance = compliance;
+        self
+    }
+
+    pub fn pin_start(mut self) -> Self {
+        self.start_anchor = RopeEnd::Pinned;
+        self
+    }
+
+    pub fn pin_end(mut self) -> Self {
+        self.end_anchor = RopeEnd::Pinned;
+        self
+    }
+
+    /// Ties the start of the rope to `entity` instead of spawning a point there. The entity
+    /// needs a `Pos`, and should sit at `start` so the first link isn't stretched.
+    pub fn pin_start_to(mut self, entity: Entity) -> Self {
+        self.start_anchor = RopeEnd::Attached(entity);
+        self
+    }
+
+    /// Ties the end of the rope to `entity`, like `pin_start_to`
+    pub fn pin_end_to(mut self, entity: Entity) -> Self {
+        self.end_anchor = RopeEnd::Attached(entity);
+        self
+    }
+
+    pub fn spawn(self, commands: &mut Commands) -> Rope {
+        let segments = (self.points - 1) as f32;
+        let spacing = self.start.distance(self.end) / segments;
+        let rest_length = self.length.map_or(spacing, |length| length / segments);
+        let radius = self.radius.unwrap_or(0.45 * spacing);
+
+        let points: Vec<Entity> = (0..self.points)
+            .map(|i| {
+                let pos = self.start.lerp(self.end, i as f32 / segments);
+                let end = match i {
+                    0 => self.start_anchor,
+                    i if i == self.points - 1 => self.end_anchor,
+                    _ => RopeEnd::Free,
+                };
+                match end {
+                    RopeEnd::Attached(entity) => entity,
+                    RopeEnd::Pinned => commands.spawn(Pos(pos)).id(),
+                    RopeEnd::Free => {
+                        spawn_particle(commands, pos, self.mass, radius, Inertia::circle(self.mass, radius))
+                    }
+                }
+            })
+            .collect();
+
+        for pair in points.windows(2) {
+            commands.spawn(DistanceConstraint {
+                a: pair[0],
+                b: pair[1],
+                rest_length,
+                compliance: self.compliance,
+            });
+        }
+
+        let rope = Rope { points };
+        commands.spawn(rope.clone());
+        rope
+    }
+}
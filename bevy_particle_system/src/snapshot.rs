@@ -0,0 +1,325 @@
+use bevy::{ecs::world::EntityMut, prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// Physics state of a whole world: every body with a `Pos`, the distance and area
+/// constraints between them, and the gravity. Rendering components aren't part of
+/// it, `restore` returns the new entities so meshes can be attached again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub gravity: Vec2,
+    pub bodies: Vec<BodySnapshot>,
+    pub distance_constraints: Vec<DistanceConstraintSnapshot>,
+    pub area_constraints: Vec<AreaConstraintSnapshot>,
+}
+
+/// The physics components of one body, `None` for the ones it doesn't have
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BodySnapshot {
+    pub pos: Pos,
+    pub prev_pos: Option<PrevPos>,
+    pub vel: Option<Vel>,
+    pub rot: Option<Rot>,
+    pub prev_rot: Option<PrevRot>,
+    pub ang_vel: Option<AngVel>,
+    pub mass: Option<Mass>,
+    pub inertia: Option<Inertia>,
+    pub restitution: Option<Restitution>,
+    pub friction: Option<Friction>,
+    pub circle: Option<CircleCollider>,
+    pub box_collider: Option<BoxCollider>,
+    pub polygon: Option<ConvexPolygonCollider>,
+    pub capsule: Option<CapsuleCollider>,
+    pub segment: Option<SegmentCollider>,
+    pub chain: Option<ChainCollider>,
+    pub granular: Option<Granular>,
+    pub fluid: bool,
+    pub kinematic: bool,
+}
+
+/// A `DistanceConstraint` between the bodies at indices `a` and `b` of `Snapshot::bodies`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistanceConstraintSnapshot {
+    pub a: usize,
+    pub b: usize,
+    pub rest_length: f32,
+    pub compliance: f32,
+    pub tearable: Option<Tearable>,
+}
+
+/// An `AreaConstraint` around the bodies at the `ring` indices of `Snapshot::bodies`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AreaConstraintSnapshot {
+    pub ring: Vec<usize>,
+    pub rest_area: f32,
+    pub compliance: f32,
+}
+
+pub(crate) type BodyItems<'a> = (
+    Entity,
+    &'a Pos,
+    Option<&'a PrevPos>,
+    Option<&'a Vel>,
+    Option<&'a Rot>,
+    Option<&'a PrevRot>,
+    Option<&'a AngVel>,
+    Option<&'a Mass>,
+    Option<&'a Inertia>,
+    Option<&'a Restitution>,
+    Option<&'a Friction>,
+);
+pub(crate) type ShapeItems<'a> = (
+    Option<&'a CircleCollider>,
+    Option<&'a BoxCollider>,
+    Option<&'a ConvexPolygonCollider>,
+    Option<&'a CapsuleCollider>,
+    Option<&'a SegmentCollider>,
+    Option<&'a ChainCollider>,
+    Option<&'a Granular>,
+    Option<&'a Fluid>,
+    Option<&'a Kinematic>,
+);
+
+impl BodySnapshot {
+    pub(crate) fn from_items(body: BodyItems, shape: ShapeItems) -> Self {
+        let (_, pos, prev_pos, vel, rot, prev_rot, ang_vel, mass, inertia, restitution, friction) = body;
+        let (circle, box_collider, polygon, capsule, segment, chain, granular, fluid, kinematic) = shape;
+        Self {
+            pos: pos.clone(),
+            prev_pos: prev_pos.cloned(),
+            vel: vel.cloned(),
+            rot: rot.cloned(),
+            prev_rot: prev_rot.cloned(),
+            ang_vel: ang_vel.cloned(),
+            mass: mass.cloned(),
+            inertia: inertia.cloned(),
+            restitution: restitution.cloned(),
+            friction: friction.cloned(),
+            circle: circle.cloned(),
+            box_collider: box_collider.cloned(),
+            polygon: polygon.cloned(),
+            capsule: capsule.cloned(),
+            segment: segment.cloned(),
+            chain: chain.cloned(),
+            granular: granular.cloned(),
+            fluid: fluid.is_some(),
+            kinematic: kinematic.is_some(),
+        }
+    }
+}
+
+impl Snapshot {
+    /// Copies the physics state out of `world`. Bodies are stored in entity order,
+    /// so capturing the same state twice gives the same snapshot.
+    pub fn capture(world: &mut World) -> Self {
+        Self::capture_with_entities(world).0
+    }
+
+    /// Like `capture`, but also returns the entities of the bodies, the distance
+    /// constraints and the area constraints, in that order
+    pub(crate) fn capture_with_entities(world: &mut World) -> (Self, Vec<Entity>) {
+        let mut bodies: Vec<(Entity, BodySnapshot)> = world
+            .query::<(BodyItems, ShapeItems)>()
+            .iter(world)
+            .map(|(body, shape)| (body.0, BodySnapshot::from_items(body, shape)))
+            .collect();
+        bodies.sort_unstable_by_key(|(entity, _)| *entity);
+        let index: HashMap<Entity, usize> = bodies.iter().enumerate().map(|(i, (entity, _))| (*entity, i)).collect();
+
+        // Constraints on bodies that have no `Pos` can't be restored, so they are left out
+        let mut distance_constraints: Vec<(Entity, DistanceConstraintSnapshot)> = world
+            .query::<(Entity, &DistanceConstraint, Option<&Tearable>)>()
+            .iter(world)
+            .filter_map(|(entity, constraint, tearable)| {
+                let snapshot = DistanceConstraintSnapshot {
+                    a: *index.get(&constraint.a)?,
+                    b: *index.get(&constraint.b)?,
+                    rest_length: constraint.rest_length,
+                    compliance: constraint.compliance,
+                    tearable: tearable.cloned(),
+                };
+                Some((entity, snapshot))
+            })
+            .collect();
+        distance_constraints.sort_unstable_by_key(|(entity, _)| *entity);
+
+        let mut area_constraints: Vec<(Entity, AreaConstraintSnapshot)> = world
+            .query::<(Entity, &AreaConstraint)>()
+            .iter(world)
+            .filter_map(|(entity, constraint)| {
+                let snapshot = AreaConstraintSnapshot {
+                    ring: constraint.ring.iter().map(|body| index.get(body).copied()).collect::<Option<_>>()?,
+                    rest_area: constraint.rest_area,
+                    compliance: constraint.compliance,
+                };
+                Some((entity, snapshot))
+            })
+            .collect();
+        area_constraints.sort_unstable_by_key(|(entity, _)| *entity);
+
+        let (mut entities, bodies): (Vec<_>, _) = bodies.into_iter().unzip();
+        let (distance_entities, distance_constraints): (Vec<_>, _) = distance_constraints.into_iter().unzip();
+        let (area_entities, area_constraints): (Vec<_>, _) = area_constraints.into_iter().unzip();
+        entities.extend(distance_entities);
+        entities.extend(area_entities);
+        let snapshot = Self {
+            gravity: world.get_resource::<Gravity>().map_or(Gravity::default().0, |gravity| gravity.0),
+            bodies,
+            distance_constraints,
+            area_constraints,
+        };
+        (snapshot, entities)
+    }
+
+    /// Keeps only the bodies `keep` returns true for. Constraints on a removed body are
+    /// removed as well, the others are renumbered to the bodies' new indices.
+    pub fn retain_bodies(&mut self, mut keep: impl FnMut(&BodySnapshot) -> bool) {
+        let mut kept = 0;
+        let index: Vec<Option<usize>> = self
+            .bodies
+            .iter()
+            .map(|body| {
+                keep(body).then(|| {
+                    kept += 1;
+                    kept - 1
+                })
+            })
+            .collect();
+        let mut i = 0;
+        self.bodies.retain(|_| {
+            i += 1;
+            index[i - 1].is_some()
+        });
+
+        self.distance_constraints.retain_mut(|constraint| match (index[constraint.a], index[constraint.b]) {
+            (Some(a), Some(b)) => {
+                (constraint.a, constraint.b) = (a, b);
+                true
+            }
+            _ => false,
+        });
+        self.area_constraints.retain_mut(|constraint| {
+            match constraint.ring.iter().map(|&i| index[i]).collect::<Option<Vec<_>>>() {
+                Some(ring) => {
+                    constraint.ring = ring;
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+
+    /// Spawns the snapshot's bodies and constraints into `world` and sets its gravity.
+    /// Existing entities are left alone. Returns the new body entities in the order
+    /// of `bodies`.
+    pub fn restore(&self, world: &mut World) -> Vec<Entity> {
+        self.restore_with(world, |world, _| world.spawn_empty().id())
+    }
+
+    /// Like `restore`, but spawns everything as the entities `capture_with_entities`
+    /// returned, which must be free in `world`
+    pub(crate) fn restore_as(&self, world: &mut World, entities: &[Entity]) {
+        self.restore_with(world, |world, i| {
+            world.get_or_spawn(entities[i]).expect("entity is already in use").id()
+        });
+    }
+
+    fn restore_with(&self, world: &mut World, mut spawn: impl FnMut(&mut World, usize) -> Entity) -> Vec<Entity> {
+        world.insert_resource(Gravity(self.gravity));
+
+        let bodies: Vec<Entity> = self
+            .bodies
+            .iter()
+            .enumerate()
+            .map(|(i, body)| {
+                let entity = spawn(world, i);
+                insert_body(&mut world.entity_mut(entity), body);
+                entity
+            })
+            .collect();
+        for (i, constraint) in self.distance_constraints.iter().enumerate() {
+            let entity = spawn(world, bodies.len() + i);
+            let mut entity = world.entity_mut(entity);
+            entity.insert(DistanceConstraint {
+                a: bodies[constraint.a],
+                b: bodies[constraint.b],
+                rest_length: constraint.rest_length,
+                compliance: constraint.compliance,
+            });
+            if let Some(tearable) = &constraint.tearable {
+                entity.insert(tearable.clone());
+            }
+        }
+        for (i, constraint) in self.area_constraints.iter().enumerate() {
+            let entity = spawn(world, bodies.len() + self.distance_constraints.len() + i);
+            world.entity_mut(entity).insert(AreaConstraint {
+                ring: constraint.ring.iter().map(|&i| bodies[i]).collect(),
+                rest_area: constraint.rest_area,
+                compliance: constraint.compliance,
+            });
+        }
+        bodies
+    }
+}
+
+impl RonFile for Snapshot {}
+
+pub(crate) fn insert_body(entity: &mut EntityMut, body: &BodySnapshot) {
+    entity.insert(body.pos.clone());
+    if let Some(prev_pos) = &body.prev_pos {
+        entity.insert(prev_pos.clone());
+    }
+    // The pre-solve velocities are rewritten by every substep before they are read
+    if let Some(vel) = &body.vel {
+        entity.insert((vel.clone(), PreSolveVel::default()));
+    }
+    if let Some(rot) = &body.rot {
+        entity.insert(rot.clone());
+    }
+    if let Some(prev_rot) = &body.prev_rot {
+        entity.insert(prev_rot.clone());
+    }
+    if let Some(ang_vel) = &body.ang_vel {
+        entity.insert((ang_vel.clone(), PreSolveAngVel::default()));
+    }
+    if let Some(mass) = &body.mass {
+        entity.insert(mass.clone());
+    }
+    if let Some(inertia) = &body.inertia {
+        entity.insert(inertia.clone());
+    }
+    if let Some(restitution) = &body.restitution {
+        entity.insert(restitution.clone());
+    }
+    if let Some(friction) = &body.friction {
+        entity.insert(friction.clone());
+    }
+    if let Some(circle) = &body.circle {
+        entity.insert(circle.clone());
+    }
+    if let Some(box_collider) = &body.box_collider {
+        entity.insert(box_collider.clone());
+    }
+    if let Some(polygon) = &body.polygon {
+        entity.insert(polygon.clone());
+    }
+    if let Some(capsule) = &body.capsule {
+        entity.insert(capsule.clone());
+    }
+    if let Some(segment) = &body.segment {
+        entity.insert(segment.clone());
+    }
+    if let Some(chain) = &body.chain {
+        entity.insert(chain.clone());
+    }
+    if let Some(granular) = &body.granular {
+        entity.insert(granular.clone());
+    }
+    if body.fluid {
+        entity.insert(Fluid);
+    }
+    if body.kinematic {
+        entity.insert(Kinematic);
+    }
+}
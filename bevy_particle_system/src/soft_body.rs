@@ -0,0 +1,125 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    builder::{polyline, spawn_particle},
+    *,
+};
+
+/// A blob spawned by `SoftBodyBuilder`. Lives on the same entity as its `AreaConstraint`.
+#[derive(Component, Debug, Clone)]
+pub struct SoftBody {
+    /// Particles around the blob, counterclockwise
+    pub points: Vec<Entity>,
+}
+
+impl SoftBody {
+    /// Current positions around the blob, ready to be drawn as a closed line strip
+    pub fn polyline(&self, positions: &Query<&Pos>) -> Vec<Vec2> {
+        polyline(&self.points, positions)
+    }
+}
+
+/// Spawns a ring of particles around `center` held together by distance constraints
+/// along its edges and an area constraint that keeps it inflated
+#[derive(Debug, Clone)]
+pub struct SoftBodyBuilder {
+    center: Vec2,
+    radius: f32,
+    points: usize,
+    particle_radius: Option<f32>,
+    mass: f32,
+    edge_compliance: f32,
+    area_compliance: f32,
+    pressure: f32,
+}
+
+impl SoftBodyBuilder {
+    pub fn new(center: Vec2, radius: f32) -> Self {
+        Self {
+            center,
+            radius,
+            points: 16,
+            particle_radius: None,
+            mass: Mass::default().0,
+            edge_compliance: 0.,
+            area_compliance: 0.,
+            pressure: 1.,
+        }
+    }
+
+    /// Number of particles around the ring
+    pub fn points(mut self, points: usize) -> Self {
+        assert!(points >= 3, "a soft body needs at least three points");
+        self.points = points;
+        self
+    }
+
+    /// Radius of the particles on the ring. Defaults to 0.45 of the edge length, which
+    /// leaves only small gaps in the outline for other bodies to slip through.
+    pub fn particle_radius(mut self, radius: f32) -> Self {
+        self.particle_radius = Some(radius);
+        self
+    }
+
+    /// Mass of one point on the ring, the whole blob weighs `points` times this
+    pub fn mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    /// Inverse stiffness of the edges between neighbouring particles
+    pub fn edge_compliance(mut self, compliance: f32) -> Self {
+        self.edge_compliance = compliance;
+        self
+    }
+
+    /// Inverse stiffness of the area, higher values make the blob squishier
+    pub fn area_compliance(mut self, compliance: f32) -> Self {
+        self.area_compliance = compliance;
+        self
+    }
+
+    /// Rest area relative to the area of the ring when spawned. Above one the blob
+    /// inflates until its edges are taut.
+    pub fn pressure(mut self, pressure: f32) -> Self {
+        self.pressure = pressure;
+        self
+    }
+
+    pub fn spawn(self, commands: &mut Commands) -> SoftBody {
+        let step = TAU / self.points as f32;
+        let spacing = 2. * self.radius * (step / 2.).sin();
+        let particle_radius = self.particle_radius.unwrap_or(0.45 * spacing);
+
+        let points: Vec<Entity> = (0..self.points)
+            .map(|i| {
+                let pos = self.center + Vec2::from_angle(step * i as f32) * self.radius;
+                let inertia = Inertia::circle(self.mass, particle_radius);
+                spawn_particle(commands, pos, self.mass, particle_radius, inertia)
+            })
+            .collect();
+
+        for i in 0..points.len() {
+            commands.spawn(DistanceConstraint {
+                a: points[i],
+                b: points[(i + 1) % points.len()],
+                rest_length: spacing,
+                compliance: self.edge_compliance,
+            });
+        }
+
+        let area = 0.5 * self.points as f32 * self.radius * self.radius * step.sin();
+        let soft_body = SoftBody { points };
+        commands.spawn((
+            soft_body.clone(),
+            AreaConstraint {
+                ring: soft_body.points.clone(),
+                rest_area: self.pressure * area,
+                compliance: self.area_compliance,
+            },
+        ));
+        soft_body
+    }
+}
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use bevy_particle_system::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn random_proxies(rng: &mut StdRng, count: u32) -> Vec<BroadPhaseProxy> {
+    (0..count)
+        .map(|i| BroadPhaseProxy {
+            entity: Entity::from_raw(i),
+            pos: Vec2::new(rng.gen_range(-10.0..10.0), rng.gen_range(-10.0..10.0)),
+            radius: rng.gen_range(0.05..0.5),
+            margin: rng.gen_range(0.0..0.2),
+        })
+        .collect()
+}
+
+fn sorted_pairs(broad_phase: &mut impl BroadPhase, proxies: &[BroadPhaseProxy]) -> Vec<(Entity, Entity)> {
+    let mut pairs = vec![];
+    broad_phase.collect_pairs(proxies, &mut pairs);
+    pairs.sort();
+    pairs
+}
+
+// All broad phases must find exactly the same pairs, in the same orientation. The same
+// instances are reused across rounds, as the plugin does, so state left over from one
+// step can't leak into the next.
+#[test]
+fn broad_phases_find_the_same_pairs() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let (mut spatial_hash, mut sweep_and_prune) = (SpatialHash::default(), SweepAndPrune::default());
+
+    for count in [0, 1, 2, 50, 400, 400, 1000] {
+        let proxies = random_proxies(&mut rng, count);
+        let expected = sorted_pairs(&mut BruteForce, &proxies);
+        if count >= 400 {
+            assert!(!expected.is_empty());
+        }
+        assert_eq!(sorted_pairs(&mut spatial_hash, &proxies), expected, "spatial hash, {count} proxies");
+        assert_eq!(sorted_pairs(&mut sweep_and_prune, &proxies), expected, "sweep and prune, {count} proxies");
+    }
+}
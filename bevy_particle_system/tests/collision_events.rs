@@ -0,0 +1,119 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use bevy_particle_system::*;
+
+/// Collects the collision events of every step, tagged with the step they were sent after
+#[derive(Default)]
+struct EventLog {
+    started: Vec<(u32, Collision)>,
+    ongoing: Vec<(u32, Collision)>,
+    ended: Vec<(u32, Collision)>,
+    step: u32,
+    readers: (
+        ManualEventReader<CollisionStarted>,
+        ManualEventReader<CollisionOngoing>,
+        ManualEventReader<CollisionEnded>,
+    ),
+}
+
+impl EventLog {
+    fn steps(&mut self, world: &mut PhysicsWorld, n: u32) {
+        for _ in 0..n {
+            world.step();
+            let step = self.step;
+            let (started, ongoing, ended) = &mut self.readers;
+            let world = &world.app.world;
+            self.started.extend(started.iter(world.resource()).map(|event: &CollisionStarted| (step, event.0)));
+            self.ongoing.extend(ongoing.iter(world.resource()).map(|event: &CollisionOngoing| (step, event.0)));
+            self.ended.extend(ended.iter(world.resource()).map(|event: &CollisionEnded| (step, event.0)));
+            self.step += 1;
+        }
+    }
+}
+
+fn floor(world: &mut PhysicsWorld) -> Entity {
+    world
+        .spawn(StaticBoxBundle {
+            pos: Pos(Vec2::new(0., -0.5)),
+            collider: BoxCollider {
+                size: Vec2::new(20., 1.),
+            },
+            ..Default::default()
+        })
+        .id()
+}
+
+fn drop_ball(world: &mut PhysicsWorld, restitution: f32) -> Entity {
+    world
+        .spawn(ParticleBundle {
+            restitution: Restitution(restitution),
+            ..ParticleBundle::new_with_pos_and_vel(Vec2::new(0., 2.), Vec2::ZERO)
+        })
+        .id()
+}
+
+// With an end delay, a ball that lands and settles after a small hop starts one
+// collision that goes on from then, and once at rest it pushes on the floor with its
+// weight every step
+#[test]
+fn landing_starts_one_collision() {
+    let mut world = PhysicsWorld::default();
+    world.insert_resource(CollisionEndDelay(0.2));
+    let floor = floor(&mut world);
+    let ball = drop_ball(&mut world, 0.);
+    let mut log = EventLog::default();
+    log.steps(&mut world, 180);
+
+    assert_eq!(log.started.len(), 1, "{:?}", log.started);
+    assert!(log.ended.is_empty(), "{:?}", log.ended);
+    let (_, collision) = log.started[0];
+    assert_eq!((collision.entity_a, collision.entity_b), (ball, floor));
+    assert!(collision.normal.y < -0.99);
+    assert!(collision.impulse > 5.);
+
+    let (_, resting) = log.ongoing.last().unwrap();
+    assert!((resting.impulse - 9.81 * DELTA_TIME).abs() < 1e-3, "{}", resting.impulse);
+}
+
+// Without an end delay, a bouncing ball ends its collision right after the last step
+// it touched the floor, and starts a new one when it comes back down
+#[test]
+fn bounce_ends_on_first_step_apart() {
+    let mut world = PhysicsWorld::default();
+    floor(&mut world);
+    drop_ball(&mut world, 0.9);
+    let mut log = EventLog::default();
+    log.steps(&mut world, 90);
+
+    assert!(log.started.len() >= 2, "{:?}", log.started);
+    assert!(!log.ended.is_empty());
+    for (end, _) in &log.ended {
+        let last_touch = log.started.iter().chain(&log.ongoing).map(|(step, _)| *step).filter(|step| step < end).max();
+        assert_eq!(last_touch, Some(end - 1));
+    }
+}
+
+// A bouncing ball ends its collision once it has been off the floor for the end
+// delay, and starts a new one when it comes back down
+#[test]
+fn bounce_ends_after_delay() {
+    let mut world = PhysicsWorld::default();
+    world.insert_resource(CollisionEndDelay(0.2));
+    floor(&mut world);
+    let ball = drop_ball(&mut world, 0.9);
+    let mut log = EventLog::default();
+    log.steps(&mut world, 90);
+
+    assert!(log.started.len() >= 2, "{:?}", log.started);
+    let (start, _) = log.started[0];
+    let (end, _) = log.ended[0];
+    let last_touch = log.ongoing.iter().map(|(step, _)| *step).filter(|step| *step < end).max().unwrap_or(start);
+    let delay = world.resource::<CollisionEndDelay>().0;
+    assert_eq!(end - last_touch, (delay / DELTA_TIME).round() as u32);
+
+    // Despawning ends the collision right away
+    log.steps(&mut world, 200);
+    let ended = log.ended.len();
+    world.app.world.despawn(ball);
+    log.steps(&mut world, 1);
+    assert_eq!(log.ended.len(), ended + 1);
+}
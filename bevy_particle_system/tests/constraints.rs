@@ -0,0 +1,143 @@
+use bevy::{ecs::system::CommandQueue, prelude::*};
+use bevy_particle_system::*;
+
+/// Counts the steps in which a static body was marked as moved
+#[derive(Resource, Default)]
+struct MovedStatics(u32);
+
+fn count_moved_statics(changed: Query<(), (Changed<Pos>, Without<Mass>)>, mut moved: ResMut<MovedStatics>) {
+    if !changed.is_empty() {
+        moved.0 += 1;
+    }
+}
+
+fn anchor(world: &mut PhysicsWorld, pos: Vec2) -> Entity {
+    world
+        .spawn(StaticBoxBundle {
+            pos: Pos(pos),
+            collider: BoxCollider {
+                size: Vec2::splat(0.1),
+            },
+            ..Default::default()
+        })
+        .id()
+}
+
+fn world_counting_moved_statics() -> PhysicsWorld {
+    let mut world = PhysicsWorld::default();
+    world.insert_resource(MovedStatics::default());
+    world.app.add_system(count_moved_statics);
+    world
+}
+
+// A particle swinging from a static anchor pulls on it every substep, but the anchor
+// must not look moved, or the static BVH is rebuilt every step
+#[test]
+fn distance_constraint_leaves_static_anchor_alone() {
+    let mut world = world_counting_moved_statics();
+    let anchor = anchor(&mut world, Vec2::ZERO);
+    let link = world
+        .spawn(ParticleBundle::new_with_pos_and_vel(Vec2::new(1., 0.), Vec2::ZERO))
+        .id();
+    world.spawn(DistanceConstraint {
+        a: anchor,
+        b: link,
+        rest_length: 1.,
+        compliance: 0.,
+    });
+
+    for _ in 0..120 {
+        world.step();
+    }
+
+    // Only the first step, which sees the anchor being spawned
+    assert_eq!(world.resource::<MovedStatics>().0, 1);
+    let length = world.get::<Pos>(link).unwrap().0.length();
+    assert!((length - 1.).abs() < 1e-3, "{length}");
+}
+
+// The same for a ring held inflated by an area constraint with one static point
+#[test]
+fn area_constraint_leaves_static_point_alone() {
+    let mut world = world_counting_moved_statics();
+    let mut ring = vec![anchor(&mut world, Vec2::ZERO)];
+    for pos in [Vec2::new(1., 0.), Vec2::new(1., 1.), Vec2::new(0., 1.)] {
+        ring.push(world.spawn(ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)).id());
+    }
+    world.spawn(AreaConstraint {
+        ring,
+        rest_area: 1.,
+        compliance: 0.,
+    });
+
+    for _ in 0..120 {
+        world.step();
+    }
+
+    assert_eq!(world.resource::<MovedStatics>().0, 1);
+}
+
+// A rope tied to an existing body links that body instead of spawning a point over it,
+// and holds it up when the other end is pinned
+#[test]
+fn rope_attached_to_body_holds_it() {
+    let mut world = PhysicsWorld::default();
+    let weight = world
+        .spawn(ParticleBundle::new_with_pos_and_vel(Vec2::new(2., 0.), Vec2::ZERO))
+        .id();
+
+    let mut queue = CommandQueue::default();
+    let rope = RopeBuilder::new(Vec2::ZERO, Vec2::new(2., 0.))
+        .points(5)
+        .pin_start()
+        .pin_end_to(weight)
+        .spawn(&mut Commands::new(&mut queue, &world.app.world));
+    queue.apply(&mut world.app.world);
+
+    assert_eq!(rope.points.len(), 5);
+    assert_eq!(*rope.points.last().unwrap(), weight);
+    assert_eq!(world.app.world.query::<&Mass>().iter(&world.app.world).count(), 4);
+
+    world.steps(240);
+
+    // Swung down, but no further from the pin than the rope reaches
+    let pos = world.get::<Pos>(weight).unwrap().0;
+    assert!(pos.y < -1., "{pos}");
+    assert!(pos.length() < 2.1, "{pos}");
+}
+
+/// A 4 by 6 sheet with 0.2 between nodes, hung from its whole top row
+fn hanging_cloth(world: &mut PhysicsWorld, tear_force: f32) -> Cloth {
+    let mut queue = CommandQueue::default();
+    let builder = (0..4).fold(ClothBuilder::new(Vec2::ZERO, 4, 6, 0.2).tear_force(tear_force), |builder, column| {
+        builder.pin(column, 0)
+    });
+    let cloth = builder.spawn(&mut Commands::new(&mut queue, &world.app.world));
+    queue.apply(&mut world.app.world);
+    cloth
+}
+
+fn link_count(world: &mut PhysicsWorld) -> usize {
+    world.app.world.query::<&DistanceConstraint>().iter(&world.app.world).count()
+}
+
+// The 20 hanging nodes weigh about 20 N, spread over the links of the top row. A cloth
+// whose links hold well above their share keeps every link and hangs still, one whose
+// links hold well below it tears and drops its lower part.
+#[test]
+fn cloth_tears_above_its_threshold() {
+    let mut strong = PhysicsWorld::default();
+    let cloth = hanging_cloth(&mut strong, 12.);
+    let links = link_count(&mut strong);
+    strong.steps(120);
+    assert_eq!(link_count(&mut strong), links);
+    let bottom = strong.get::<Pos>(cloth.point(0, 5)).unwrap().0;
+    assert!((bottom.y + 1.).abs() < 1e-2, "{bottom}");
+
+    let mut weak = PhysicsWorld::default();
+    let cloth = hanging_cloth(&mut weak, 3.);
+    weak.steps(120);
+    assert!(link_count(&mut weak) < links);
+    let bottom = weak.get::<Pos>(cloth.point(0, 5)).unwrap().0;
+    assert!(bottom.y < -3., "{bottom}");
+}
@@ -0,0 +1,524 @@
+use bevy::prelude::*;
+use bevy_particle_system::*;
+
+// Two particles of different mass collide head on. Afterwards the total momentum
+// must be unchanged while the relative velocity is reversed and scaled by the
+// restitution coefficient.
+#[test]
+fn particle_collision_conserves_momentum() {
+    let restitution = 0.5;
+
+    let mut world = PhysicsWorld::default();
+    world.insert_resource(Gravity(Vec2::ZERO));
+
+    let left = world
+        .spawn(ParticleBundle {
+            mass: Mass(3.),
+            restitution: Restitution(restitution),
+            ..ParticleBundle::new_with_pos_and_vel(Vec2::new(-2., 0.), Vec2::new(2., 0.))
+        })
+        .id();
+    let right = world
+        .spawn(ParticleBundle {
+            mass: Mass(1.),
+            restitution: Restitution(restitution),
+            ..ParticleBundle::new_with_pos_and_vel(Vec2::new(2., 0.), Vec2::new(-2., 0.))
+        })
+        .id();
+
+    let momentum = |world: &PhysicsWorld| {
+        let vel_a = world.get::<Vel>(left).unwrap().0;
+        let vel_b = world.get::<Vel>(right).unwrap().0;
+        (3. * vel_a + vel_b, vel_a - vel_b)
+    };
+    let (momentum_before, relative_vel_before) = momentum(&world);
+
+    world.steps(120);
+
+    let (momentum_after, relative_vel_after) = momentum(&world);
+    assert!((momentum_after - momentum_before).length() < 1e-3, "{momentum_before} -> {momentum_after}");
+    assert!(
+        (relative_vel_after + restitution * relative_vel_before).length() < 1e-3,
+        "{relative_vel_before} -> {relative_vel_after}"
+    );
+}
+
+fn floor(world: &mut PhysicsWorld, pos: Vec2, rot: f32) {
+    world.spawn(StaticBoxBundle {
+        pos: Pos(pos),
+        rot: Rot(rot),
+        collider: BoxCollider {
+            size: Vec2::new(20., 1.),
+        },
+        ..Default::default()
+    });
+}
+
+// A particle and a dynamic box dropped onto a static floor must come to rest on
+// top of it without sinking in or bouncing off
+#[test]
+fn bodies_rest_on_static_floor() {
+    let mut world = PhysicsWorld::default();
+    floor(&mut world, Vec2::new(0., -0.5), 0.);
+    let ball = world
+        .spawn(ParticleBundle {
+            restitution: Restitution(0.),
+            ..ParticleBundle::new_with_pos_and_vel(Vec2::new(-2., 2.), Vec2::ZERO)
+        })
+        .id();
+    let crate_box = world
+        .spawn(DynamicBoxBundle {
+            restitution: Restitution(0.),
+            ..DynamicBoxBundle::new_with_pos_and_vel(Vec2::new(2., 2.), Vec2::ZERO)
+        })
+        .id();
+
+    world.steps(180);
+
+    // Both default to a half extent of 0.5 and the floor's top is at 0
+    for body in [ball, crate_box] {
+        let pos = world.get::<Pos>(body).unwrap().0;
+        let vel = world.get::<Vel>(body).unwrap().0;
+        assert!((pos.y - 0.5).abs() < 1e-2, "{pos}");
+        assert!(vel.length() < 1e-2, "{vel}");
+    }
+}
+
+/// A static trapezoid with its 10 wide top at y = 0
+fn polygon_floor(world: &mut PhysicsWorld) {
+    world.spawn(StaticPolygonBundle {
+        collider: ConvexPolygonCollider::new(vec![
+            Vec2::new(-6., -1.),
+            Vec2::new(6., -1.),
+            Vec2::new(5., 0.),
+            Vec2::new(-5., 0.),
+        ]),
+        ..Default::default()
+    });
+}
+
+// A hexagon dropped onto a static polygon must come to rest on one of its flat sides
+#[test]
+fn polygon_rests_on_static_polygon() {
+    let mut world = PhysicsWorld::default();
+    polygon_floor(&mut world);
+    let collider = ConvexPolygonCollider::regular(6, 0.5);
+    let hexagon = world
+        .spawn(DynamicPolygonBundle {
+            inertia: Inertia::polygon(1., &collider),
+            collider,
+            restitution: Restitution(0.),
+            ..DynamicPolygonBundle::new_with_pos_and_vel(Vec2::new(0., 2.), Vec2::ZERO)
+        })
+        .id();
+
+    world.steps(180);
+
+    // The flat sides of the hexagon are 0.5 * sin(60°) from its center
+    let pos = world.get::<Pos>(hexagon).unwrap().0;
+    let vel = world.get::<Vel>(hexagon).unwrap().0;
+    assert!((pos.y - 0.5 * 60f32.to_radians().sin()).abs() < 1e-2, "{pos}");
+    assert!(vel.length() < 1e-2, "{vel}");
+}
+
+// A particle dropped onto a static polygon must come to rest on top of it
+#[test]
+fn particle_rests_on_static_polygon() {
+    let mut world = PhysicsWorld::default();
+    polygon_floor(&mut world);
+    let ball = world
+        .spawn(ParticleBundle {
+            restitution: Restitution(0.),
+            ..ParticleBundle::new_with_pos_and_vel(Vec2::new(1., 2.), Vec2::ZERO)
+        })
+        .id();
+
+    world.steps(180);
+
+    let pos = world.get::<Pos>(ball).unwrap().0;
+    let vel = world.get::<Vel>(ball).unwrap().0;
+    assert!((pos.y - 0.5).abs() < 1e-2, "{pos}");
+    assert!(vel.length() < 1e-2, "{vel}");
+}
+
+// Polygons with fewer than three vertices have nothing to collide with, bodies fall
+// through them instead of crashing the narrow phase
+#[test]
+fn degenerate_polygons_have_no_contacts() {
+    let mut world = PhysicsWorld::default();
+    floor(&mut world, Vec2::new(0., -5.5), 0.);
+    world.spawn(StaticPolygonBundle {
+        collider: ConvexPolygonCollider {
+            vertices: vec![Vec2::new(-5., 0.), Vec2::new(5., 0.)],
+        },
+        ..Default::default()
+    });
+    let ball = world.spawn(ParticleBundle::new_with_pos_and_vel(Vec2::new(0., 1.), Vec2::ZERO)).id();
+    let empty = world
+        .spawn(DynamicPolygonBundle {
+            collider: ConvexPolygonCollider { vertices: vec![] },
+            inertia: Inertia(1.),
+            ..DynamicPolygonBundle::new_with_pos_and_vel(Vec2::new(3., 1.), Vec2::ZERO)
+        })
+        .id();
+
+    world.steps(120);
+
+    // The ball lands on the floor below the segment, the empty polygon falls through it
+    assert!((world.get::<Pos>(ball).unwrap().0.y + 4.5).abs() < 1e-2);
+    assert!(world.get::<Pos>(empty).unwrap().0.y < -6.);
+}
+
+// A frictionless particle dropped onto a tilted floor must slide down it
+#[test]
+fn frictionless_particle_slides_down_slope() {
+    let mut world = PhysicsWorld::default();
+    floor(&mut world, Vec2::new(0., -10.), 0.3);
+    let slider = world
+        .spawn(ParticleBundle {
+            friction: Friction {
+                static_coeff: 0.,
+                dynamic_coeff: 0.,
+            },
+            ..ParticleBundle::new_with_pos_and_vel(Vec2::new(0., -7.), Vec2::ZERO)
+        })
+        .id();
+
+    world.steps(180);
+
+    // Towards the lower end, to the left
+    let vel = world.get::<Vel>(slider).unwrap().0;
+    assert!(vel.x < -1., "{vel}");
+}
+
+// A particle on a tilted floor with the default friction must roll down it: moving
+// downhill and turning counterclockwise at the speed that keeps it from slipping
+#[test]
+fn particle_rolls_down_incline() {
+    let mut world = PhysicsWorld::default();
+    let (center, angle) = (Vec2::new(0., -10.), 0.3);
+    floor(&mut world, center, angle);
+    let normal = Vec2::from_angle(angle).rotate(Vec2::Y);
+    let ball = world
+        .spawn(ParticleBundle::new_with_pos_and_vel(center + normal * 1.01, Vec2::ZERO))
+        .id();
+
+    world.steps(120);
+
+    let vel = world.get::<Vel>(ball).unwrap().0;
+    let ang_vel = world.get::<AngVel>(ball).unwrap().0;
+    assert!(vel.x < -1., "{vel}");
+    assert!(Vec2::dot(vel, normal).abs() < 1e-2, "{vel}");
+    // Rolling without slipping, with the default radius of 0.5
+    assert!((ang_vel * 0.5 - vel.length()).abs() < 0.1 * vel.length(), "{ang_vel} {vel}");
+}
+
+// Friction is optional, a particle spawned without it still lands on the floor
+#[test]
+fn body_without_friction_collides() {
+    let mut world = PhysicsWorld::default();
+    floor(&mut world, Vec2::new(0., -0.5), 0.);
+    let mut ball = world.spawn(ParticleBundle {
+        restitution: Restitution(0.),
+        ..ParticleBundle::new_with_pos_and_vel(Vec2::new(0., 2.), Vec2::ZERO)
+    });
+    ball.remove::<Friction>();
+    let ball = ball.id();
+
+    world.steps(120);
+
+    let pos = world.get::<Pos>(ball).unwrap().0;
+    assert!((pos.y - 0.5).abs() < 1e-2, "{pos}");
+}
+
+// Bodies with only the components ParticleBundle started out with, no rotation or
+// friction, still collide with the floor and with each other
+#[test]
+fn body_without_rotation_collides() {
+    let mut world = PhysicsWorld::default();
+    floor(&mut world, Vec2::new(0., -0.5), 0.);
+    let mut spawn = |pos: Vec2| {
+        world
+            .spawn((
+                Pos(pos),
+                PrevPos(pos),
+                Mass(1.),
+                CircleCollider { radius: 0.5 },
+                Vel(Vec2::ZERO),
+                PreSolveVel::default(),
+                Restitution(0.),
+            ))
+            .id()
+    };
+    let bottom = spawn(Vec2::new(0., 1.));
+    let top = spawn(Vec2::new(0., 3.));
+
+    world.steps(120);
+
+    let bottom = world.get::<Pos>(bottom).unwrap().0;
+    let top = world.get::<Pos>(top).unwrap().0;
+    assert!((bottom.y - 0.5).abs() < 1e-2, "{bottom}");
+    assert!((top.y - 1.5).abs() < 2e-2, "{top}");
+}
+
+// Restitution is optional too, a static floor spawned without it is bounced off
+// with the default instead of failing the velocity solve
+#[test]
+fn static_without_restitution_collides() {
+    let mut world = PhysicsWorld::default();
+    world.spawn((
+        Pos(Vec2::new(0., -0.5)),
+        BoxCollider {
+            size: Vec2::new(20., 1.),
+        },
+        Friction::default(),
+    ));
+    let ball = world
+        .spawn(ParticleBundle::new_with_pos_and_vel(Vec2::new(0., 2.), Vec2::ZERO))
+        .id();
+
+    world.steps(30);
+    // Still falling before it lands, bouncing back up right after
+    assert!(world.get::<Vel>(ball).unwrap().0.y < 0.);
+    world.steps(10);
+    assert!(world.get::<Vel>(ball).unwrap().0.y > 0.);
+}
+
+fn terrain(world: &mut PhysicsWorld, vertices: Vec<Vec2>, friction: Friction) {
+    world.spawn(StaticChainBundle {
+        collider: ChainCollider::open(vertices),
+        friction,
+        ..Default::default()
+    });
+}
+
+// A box landing on a peak in the middle of a chain is held up by the joint, which both
+// segments next to it see from the box's face instead of their own normal
+#[test]
+fn box_rests_on_chain_peak() {
+    let mut world = PhysicsWorld::default();
+    let hills = vec![
+        Vec2::new(-6., -2.),
+        Vec2::new(-3., -1.),
+        Vec2::ZERO,
+        Vec2::new(3., -1.),
+        Vec2::new(6., -2.),
+    ];
+    terrain(&mut world, hills, Friction::default());
+    let crate_box = world
+        .spawn(DynamicBoxBundle {
+            restitution: Restitution(0.),
+            ..DynamicBoxBundle::new_with_pos_and_vel(Vec2::new(0., 1.), Vec2::ZERO)
+        })
+        .id();
+
+    world.steps(120);
+
+    let pos = world.get::<Pos>(crate_box).unwrap().0;
+    assert!((pos - Vec2::new(0., 0.5)).length() < 1e-2, "{pos}");
+}
+
+/// Checks that `body` keeps moving right at 3 m/s at the same height, as it would on a
+/// single long segment
+fn assert_crosses_joints(world: &mut PhysicsWorld, body: Entity, height: f32) {
+    for _ in 0..60 {
+        world.step();
+        let pos = world.get::<Pos>(body).unwrap().0;
+        let vel = world.get::<Vel>(body).unwrap().0;
+        assert!((pos.y - height).abs() < 1e-2, "{pos}");
+        assert!((vel - Vec2::new(3., 0.)).length() < 5e-2, "{vel}");
+    }
+}
+
+fn flat_terrain(world: &mut PhysicsWorld, friction: Friction) {
+    terrain(world, (-10..=10).map(|x| Vec2::new(x as f32 * 0.5, 0.)).collect(), friction);
+}
+
+// A ball rolling along a flat chain crosses the joints between its segments without
+// catching on them
+#[test]
+fn particle_rolls_across_chain_joints() {
+    let mut world = PhysicsWorld::default();
+    flat_terrain(&mut world, Friction::default());
+    let ball = world
+        .spawn(ParticleBundle {
+            ang_vel: AngVel(-3. / 0.5),
+            ..ParticleBundle::new_with_pos_and_vel(Vec2::new(-4., 0.5), Vec2::new(3., 0.))
+        })
+        .id();
+
+    assert_crosses_joints(&mut world, ball, 0.5);
+}
+
+// The same for a box sliding without friction
+#[test]
+fn box_slides_across_chain_joints() {
+    let frictionless = Friction {
+        static_coeff: 0.,
+        dynamic_coeff: 0.,
+    };
+    let mut world = PhysicsWorld::default();
+    flat_terrain(&mut world, frictionless.clone());
+    let crate_box = world
+        .spawn(DynamicBoxBundle {
+            friction: frictionless,
+            collider: BoxCollider {
+                size: Vec2::splat(0.3),
+            },
+            inertia: Inertia::rectangle(1., Vec2::splat(0.3)),
+            ..DynamicBoxBundle::new_with_pos_and_vel(Vec2::new(-4., 0.15), Vec2::new(3., 0.))
+        })
+        .id();
+
+    assert_crosses_joints(&mut world, crate_box, 0.15);
+}
+
+/// A static upright capsule with its top cap centered on (0, 0.5) and a box whose lower
+/// left corner is at `corner` from that center
+fn capsule_and_box(world: &mut PhysicsWorld, corner: Vec2) -> Entity {
+    world.insert_resource(Gravity(Vec2::ZERO));
+    world.spawn(StaticCapsuleBundle {
+        collider: CapsuleCollider { length: 1., radius: 0.25 },
+        ..Default::default()
+    });
+    let pos = Vec2::new(0., 0.5) + corner + Vec2::splat(0.5);
+    world.spawn(DynamicBoxBundle::new_with_pos_and_vel(pos, Vec2::ZERO)).id()
+}
+
+// A box whose corner sits diagonally off a capsule's end cap, inside the reach of both
+// shapes along every edge normal but not touching the rounded cap, must stay where it is
+#[test]
+fn box_corner_near_capsule_cap_stays_apart() {
+    let mut world = PhysicsWorld::default();
+    let corner = Vec2::new(0.2, 0.2);
+    let crate_box = capsule_and_box(&mut world, corner);
+    let start = world.get::<Pos>(crate_box).unwrap().0;
+    assert!(corner.length() > 0.25);
+
+    world.steps(30);
+
+    let pos = world.get::<Pos>(crate_box).unwrap().0;
+    assert!((pos - start).length() < 1e-5, "{pos}");
+    assert!(world.get::<Vel>(crate_box).unwrap().0.length() < 1e-5);
+}
+
+// A box corner pressed diagonally into a capsule's end cap, beyond the ends of both
+// facing edges, must be pushed out along the line to the cap's center
+#[test]
+fn box_corner_in_capsule_cap_is_pushed_out() {
+    let mut world = PhysicsWorld::default();
+    let crate_box = capsule_and_box(&mut world, Vec2::new(0.15, 0.15));
+
+    world.steps(30);
+
+    let corner = world.get::<Pos>(crate_box).unwrap().0 - Vec2::new(0., 0.5) - Vec2::splat(0.5);
+    assert!(corner.length() > 0.249, "{corner}");
+    assert!((corner.x - corner.y).abs() < 1e-3, "{corner}");
+}
+
+// A level of a hundred static pillars at different heights with a particle dropped onto
+// each. Every particle must land on its own pillar, including one whose pillar is raised
+// after the first step, which only works if the static BVH is rebuilt.
+#[test]
+fn particles_land_on_many_static_boxes() {
+    let mut world = PhysicsWorld::default();
+    let top = |i: usize| (i % 7) as f32;
+    let mut pillars = Vec::new();
+    let mut balls = Vec::new();
+    for i in 0..100 {
+        let x = i as f32 * 2.;
+        pillars.push(
+            world
+                .spawn(StaticBoxBundle {
+                    pos: Pos(Vec2::new(x, top(i) - 0.5)),
+                    collider: BoxCollider {
+                        size: Vec2::new(1.5, 1.),
+                    },
+                    ..Default::default()
+                })
+                .id(),
+        );
+        balls.push(
+            world
+                .spawn(ParticleBundle {
+                    restitution: Restitution(0.),
+                    ..ParticleBundle::new_with_pos_and_vel(Vec2::new(x, top(i) + 2.), Vec2::ZERO)
+                })
+                .id(),
+        );
+    }
+
+    world.step();
+    world.get_mut::<Pos>(pillars[0]).unwrap().0.y += 1.;
+    world.steps(180);
+
+    for (i, ball) in balls.into_iter().enumerate() {
+        let pos = world.get::<Pos>(ball).unwrap().0;
+        let raised = if i == 0 { 1. } else { 0. };
+        assert!((pos.x - i as f32 * 2.).abs() < 1e-2, "{i}: {pos}");
+        assert!((pos.y - (top(i) + raised + 0.5)).abs() < 1e-2, "{i}: {pos}");
+    }
+}
+
+fn kinematic_box(world: &mut PhysicsWorld, pos: Vec2, size: Vec2, vel: Vec2) -> Entity {
+    world
+        .spawn((
+            StaticBoxBundle {
+                pos: Pos(pos),
+                collider: BoxCollider { size },
+                ..Default::default()
+            },
+            KinematicBundle::new(vel, 0.),
+        ))
+        .id()
+}
+
+// A kinematic paddle sweeping through a heavy particle keeps its velocity and path,
+// while the particle is pushed ahead of it at least as fast as the paddle moves
+#[test]
+fn kinematic_paddle_pushes_without_being_pushed() {
+    let mut world = PhysicsWorld::default();
+    world.insert_resource(Gravity(Vec2::ZERO));
+    let paddle = kinematic_box(&mut world, Vec2::new(-2., 0.), Vec2::new(0.2, 2.), Vec2::new(2., 0.));
+    let ball = world
+        .spawn(ParticleBundle {
+            mass: Mass(100.),
+            inertia: Inertia::circle(100., 0.5),
+            ..ParticleBundle::new_with_pos_and_vel(Vec2::ZERO, Vec2::ZERO)
+        })
+        .id();
+
+    world.steps(60);
+
+    // One second at 2 m/s
+    let paddle_pos = world.get::<Pos>(paddle).unwrap().0;
+    assert!((paddle_pos - Vec2::ZERO).length() < 1e-4, "{paddle_pos}");
+    assert_eq!(world.get::<Vel>(paddle).unwrap().0, Vec2::new(2., 0.));
+    let ball_pos = world.get::<Pos>(ball).unwrap().0;
+    assert!(ball_pos.x >= 0.6 - 1e-2, "{ball_pos}");
+    assert!(world.get::<Vel>(ball).unwrap().0.x >= 2. - 1e-2);
+}
+
+// A box resting on a kinematic platform is carried along by friction, a particle
+// would roll instead
+#[test]
+fn kinematic_platform_carries_box() {
+    let mut world = PhysicsWorld::default();
+    let platform = kinematic_box(&mut world, Vec2::new(0., -0.5), Vec2::new(10., 1.), Vec2::new(1., 0.));
+    let crate_box = world
+        .spawn(DynamicBoxBundle {
+            restitution: Restitution(0.),
+            ..DynamicBoxBundle::new_with_pos_and_vel(Vec2::new(0., 0.5), Vec2::ZERO)
+        })
+        .id();
+
+    world.steps(120);
+
+    let platform_pos = world.get::<Pos>(platform).unwrap().0;
+    let box_pos = world.get::<Pos>(crate_box).unwrap().0;
+    let box_vel = world.get::<Vel>(crate_box).unwrap().0;
+    assert!((box_pos.y - 0.5).abs() < 1e-2, "{box_pos}");
+    assert!((box_vel - Vec2::X).length() < 1e-2, "{box_vel}");
+    // It only lagged behind while it got up to speed
+    assert!((box_pos.x - platform_pos.x).abs() < 0.2, "{box_pos} {platform_pos}");
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use bevy_particle_system::*;
+use rand::Rng;
+
+// The same scene of marbles, boxes and water is simulated twice in deterministic
+// mode, and the state hashes of both runs must match after every step
+#[test]
+fn runs_hash_the_same_every_step() {
+    let mut first = setup();
+    let mut second = setup();
+
+    for _ in 0..300 {
+        first.step();
+        second.step();
+        let (a, b) = (*first.resource::<StateHash>(), *second.resource::<StateHash>());
+        assert_eq!(a, b, "runs diverged at step {}", a.step);
+    }
+}
+
+fn setup() -> PhysicsWorld {
+    let mut world = PhysicsWorld::new(XPBDPlugin {
+        deterministic: true,
+        ..Default::default()
+    });
+
+    let walls = [
+        (Vec2::new(0., -3.5), Vec2::new(9., 1.)),
+        (Vec2::new(-4.5, 0.), Vec2::new(1., 8.)),
+        (Vec2::new(4.5, 0.), Vec2::new(1., 8.)),
+    ];
+    for (pos, size) in walls {
+        world.spawn(StaticBoxBundle {
+            pos: Pos(pos),
+            collider: BoxCollider { size },
+            ..Default::default()
+        });
+    }
+
+    // Jitter from the seeded generator, so both runs get the same spawn positions
+    let jitter: Vec<Vec2> = {
+        let mut rng = world.app.world.resource_mut::<PhysicsRng>();
+        (0..200)
+            .map(|_| Vec2::new(rng.0.gen::<f32>() - 0.5, rng.0.gen::<f32>() - 0.5) * 0.05)
+            .collect()
+    };
+    let mut jitter = jitter.into_iter();
+
+    for i in 0..60 {
+        let pos = Vec2::new(-3.5 + (i % 10) as f32 * 0.3, 1. + (i / 10) as f32 * 0.3);
+        world.spawn(ParticleBundle {
+            collider: CircleCollider { radius: 0.1 },
+            inertia: Inertia::circle(1., 0.1),
+            ..ParticleBundle::new_with_pos_and_vel(pos + jitter.next().unwrap(), Vec2::ZERO)
+        });
+    }
+    for i in 0..4 {
+        let pos = Vec2::new(-2. + i as f32 * 1.2, 3.5);
+        world.spawn(DynamicBoxBundle {
+            collider: BoxCollider {
+                size: Vec2::splat(0.4),
+            },
+            inertia: Inertia::rectangle(1., Vec2::splat(0.4)),
+            ..DynamicBoxBundle::new_with_pos_and_vel(pos + jitter.next().unwrap(), Vec2::ZERO)
+        });
+    }
+    for i in 0..100 {
+        let pos = Vec2::new(1. + (i % 10) as f32 * 0.2, -2.9 + (i / 10) as f32 * 0.2);
+        world.spawn((
+            ParticleBundle {
+                collider: CircleCollider { radius: 0.1 },
+                inertia: Inertia::circle(1., 0.1),
+                restitution: Restitution(0.),
+                ..ParticleBundle::new_with_pos_and_vel(pos + jitter.next().unwrap(), Vec2::ZERO)
+            },
+            Fluid,
+        ));
+    }
+    world
+}
@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+use bevy_particle_system::*;
+
+fn wall(world: &mut PhysicsWorld, pos: Vec2, size: Vec2) {
+    world.spawn(StaticBoxBundle {
+        pos: Pos(pos),
+        collider: BoxCollider { size },
+        ..Default::default()
+    });
+}
+
+// Fluid particles dropped into a tank from a sparse grid fall together until they reach
+// the rest density and then hold it. The density is counted in a window away from the
+// walls, the floor and the surface, where particles miss neighbours. Small, frictionless
+// particles without the density constraint would pack about four times as tight.
+#[test]
+fn fluid_settles_at_rest_density() {
+    let mut world = PhysicsWorld::default();
+    wall(&mut world, Vec2::new(0., -0.5), Vec2::new(6., 1.));
+    wall(&mut world, Vec2::new(-2.5, 3.), Vec2::new(1., 8.));
+    wall(&mut world, Vec2::new(2.5, 3.), Vec2::new(1., 8.));
+    let particles: Vec<Entity> = (0..195)
+        .map(|i| {
+            let pos = Vec2::new(-1.8 + (i % 13) as f32 * 0.3, 0.1 + (i / 13) as f32 * 0.3);
+            world
+                .spawn((
+                    ParticleBundle {
+                        collider: CircleCollider { radius: 0.05 },
+                        inertia: Inertia::circle(1., 0.05),
+                        restitution: Restitution(0.),
+                        friction: Friction {
+                            static_coeff: 0.,
+                            dynamic_coeff: 0.,
+                        },
+                        ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+                    },
+                    Fluid,
+                ))
+                .id()
+        })
+        .collect();
+
+    world.steps(480);
+
+    let (window_min, window_max) = (Vec2::new(-1.2, 0.4), Vec2::new(1.2, 1.2));
+    let inside = particles
+        .iter()
+        .map(|particle| world.get::<Pos>(*particle).unwrap().0)
+        .filter(|pos| pos.cmpge(window_min).all() && pos.cmplt(window_max).all())
+        .count();
+    // Every particle weighs 1 kg
+    let size = window_max - window_min;
+    let density = inside as f32 / (size.x * size.y);
+    let rest_density = world.resource::<FluidSettings>().rest_density;
+    assert!((density / rest_density - 1.).abs() < 0.2, "{density}");
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use bevy_particle_system::*;
+
+/// Stacks 55 grains into a triangle on a wide floor, with or without `Granular`
+fn pile(world: &mut PhysicsWorld, granular: bool) -> Vec<Entity> {
+    world.spawn(StaticBoxBundle {
+        pos: Pos(Vec2::new(0., -0.5)),
+        collider: BoxCollider {
+            size: Vec2::new(20., 1.),
+        },
+        ..Default::default()
+    });
+    let row_height = 0.2 * 0.75f32.sqrt();
+    (0..10)
+        .flat_map(|row| (0..10 - row).map(move |column| (row, column)))
+        .map(|(row, column)| {
+            let pos = Vec2::new(-0.9 + row as f32 * 0.1 + column as f32 * 0.2, 0.1 + row as f32 * row_height);
+            let mut grain = world.spawn(ParticleBundle {
+                collider: CircleCollider { radius: 0.1 },
+                inertia: Inertia::circle(1., 0.1),
+                restitution: Restitution(0.),
+                ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+            });
+            if granular {
+                grain.insert(Granular::default());
+            }
+            grain.id()
+        })
+        .collect()
+}
+
+/// Height of the highest grain, how far the furthest one got from the middle and the
+/// speed of the fastest one
+fn measure(world: &PhysicsWorld, grains: &[Entity]) -> (f32, f32, f32) {
+    grains.iter().fold((0., 0., 0.), |(top, spread, speed), grain| {
+        let pos = world.get::<Pos>(*grain).unwrap().0;
+        let vel = world.get::<Vel>(*grain).unwrap().0;
+        (top.max(pos.y), spread.max(pos.x.abs()), speed.max(vel.length()))
+    })
+}
+
+// A triangle of marbles collapses and keeps rolling apart until it lies flat, while the
+// same triangle of granular grains slumps into a lower pile that holds still
+#[test]
+fn granular_pile_holds_together() {
+    let mut grains_world = PhysicsWorld::default();
+    let grains = pile(&mut grains_world, true);
+    grains_world.steps(240);
+    let (top, spread, speed) = measure(&grains_world, &grains);
+    assert!(top > 0.3, "{top}");
+    assert!(spread < 4., "{spread}");
+    assert!(speed < 0.05, "{speed}");
+
+    let mut marbles_world = PhysicsWorld::default();
+    let marbles = pile(&mut marbles_world, false);
+    marbles_world.steps(240);
+    let (top, spread, speed) = measure(&marbles_world, &marbles);
+    assert!(top < 0.15, "{top}");
+    assert!(spread > 4., "{spread}");
+    assert!(speed > 1., "{speed}");
+}
@@ -0,0 +1,177 @@
+use std::time::{Duration, Instant};
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use bevy_particle_system::*;
+use rand::random;
+
+// A session driven by an uneven frame rate spawns marbles at random and despawns
+// the ones that fall off, like marble_pour does from the mouse. It is recorded,
+// written to a file and played back, and every marble must end up exactly where
+// it did live.
+#[test]
+fn playback_matches_live_session() {
+    let mut live = PhysicsWorld::new(XPBDPlugin {
+        deterministic: true,
+        ..Default::default()
+    });
+    // Stepped by the clock like a windowed app, not one step per update
+    live.insert_resource(PhysicsStepping::RealTime);
+    live.app.add_system(spawn_marbles).add_system(despawn_fallen);
+
+    // Non-physics entities shouldn't confuse the recording
+    live.app.world.spawn_empty();
+    live.spawn(StaticBoxBundle {
+        pos: Pos(Vec2::new(-1., -2.)),
+        rot: Rot(-0.4),
+        collider: BoxCollider {
+            size: Vec2::new(4., 0.5),
+        },
+        ..Default::default()
+    });
+
+    let mut now = Instant::now();
+    let mut frame = |live: &mut PhysicsWorld, frame: u32| {
+        // Alternate between fast and slow frames, so some run no step and some several
+        now += Duration::from_secs_f32(if frame % 7 < 4 { 1. / 144. } else { 1. / 25. });
+        live.insert_resource(TimeUpdateStrategy::ManualInstant(now));
+        live.app.update();
+    };
+    frame(&mut live, 0);
+
+    PhysicsRecorder::start(&mut live.app.world);
+    for i in 1..600 {
+        frame(&mut live, i);
+    }
+    let recording = PhysicsRecorder::stop(&mut live.app.world).unwrap();
+    assert!(recording.steps > 0);
+    assert!(recording.commands.iter().any(|command| matches!(command, RecordedCommand::Despawn { .. })));
+
+    let path = std::env::temp_dir().join("bevy_particle_system_recording_test.ron");
+    recording.save(&path).unwrap();
+    let mut playback = Playback::new(Recording::load(&path).unwrap());
+    playback.run();
+
+    let live_state = Snapshot::capture(&mut live.app.world);
+    let played_state = Snapshot::capture(&mut playback.world.app.world);
+    assert_eq!(live_state.bodies.len(), played_state.bodies.len());
+    for (a, b) in live_state.bodies.iter().zip(&played_state.bodies) {
+        assert_eq!(a.pos.0, b.pos.0);
+        assert_eq!(a.vel.as_ref().map(|vel| vel.0), b.vel.as_ref().map(|vel| vel.0));
+    }
+    assert_eq!(live.resource::<StateHash>().hash, playback.world.resource::<StateHash>().hash);
+}
+
+// An exclusive system swaps a marble for a new one every few steps, like loading a
+// snapshot in marble_pour does. The new marble reuses the entity of the despawned one
+// in the same frame, which playback must handle in the same order.
+#[test]
+fn playback_handles_reused_entities() {
+    let mut live = PhysicsWorld::new(XPBDPlugin {
+        deterministic: true,
+        ..Default::default()
+    });
+    live.app.add_system(replace_marble);
+    for i in 0..5 {
+        live.spawn(ParticleBundle::new_with_pos_and_vel(Vec2::new(i as f32, 0.), Vec2::ZERO));
+    }
+
+    PhysicsRecorder::start(&mut live.app.world);
+    for _ in 0..60 {
+        live.step();
+    }
+    let recording = PhysicsRecorder::stop(&mut live.app.world).unwrap();
+
+    let reused = recording.commands.windows(2).any(|pair| match pair {
+        [RecordedCommand::Despawn { step, entity }, RecordedCommand::Spawn { step: spawn_step, entity: spawned, .. }] => {
+            step == spawn_step && entity.index() == spawned.index()
+        }
+        _ => false,
+    });
+    assert!(reused, "{:?}", recording.commands);
+
+    let mut playback = Playback::new(recording);
+    playback.run();
+    assert_eq!(live.resource::<StateHash>().hash, playback.world.resource::<StateHash>().hash);
+}
+
+// A rope tears in one place before recording starts, which moves its last link in
+// front of the others in the world's storage. Playback spawns the links in entity
+// order, and must still solve them in the same order as the live world.
+#[test]
+fn playback_matches_torn_rope() {
+    let mut live = PhysicsWorld::new(XPBDPlugin {
+        deterministic: true,
+        ..Default::default()
+    });
+    let mut points = vec![live.spawn(Pos(Vec2::ZERO)).id()];
+    for i in 1..10 {
+        let pos = Vec2::new(i as f32 * 0.3, 0.);
+        points.push(live.spawn(ParticleBundle {
+            collider: CircleCollider { radius: 0.1 },
+            ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+        }).id());
+    }
+    for (i, pair) in points.windows(2).enumerate() {
+        live.spawn((
+            DistanceConstraint {
+                a: pair[0],
+                b: pair[1],
+                rest_length: 0.3,
+                compliance: 0.,
+            },
+            // The fifth link tears as soon as the rope swings down
+            Tearable {
+                max_force: if i == 4 { 10. } else { 1e6 },
+            },
+        ));
+    }
+    for _ in 0..30 {
+        live.step();
+    }
+    assert_eq!(live.app.world.query::<&DistanceConstraint>().iter(&live.app.world).count(), 8);
+
+    PhysicsRecorder::start(&mut live.app.world);
+    for _ in 0..120 {
+        live.step();
+    }
+    let mut playback = Playback::new(PhysicsRecorder::stop(&mut live.app.world).unwrap());
+    playback.run();
+    assert_eq!(live.resource::<StateHash>().hash, playback.world.resource::<StateHash>().hash);
+}
+
+fn replace_marble(world: &mut World, mut frame: Local<u32>) {
+    // Every tenth frame
+    *frame += 1;
+    if *frame < 10 {
+        return;
+    }
+    *frame = 0;
+    let oldest = world.query_filtered::<Entity, With<Mass>>().iter(world).min().unwrap();
+    let pos = world.get::<Pos>(oldest).unwrap().0 + Vec2::new(0., 3.);
+    world.despawn(oldest);
+    world.spawn(ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO));
+}
+
+fn spawn_marbles(mut commands: Commands, mut frame: Local<u32>) {
+    // Every third frame
+    *frame += 1;
+    if *frame < 3 {
+        return;
+    }
+    *frame = 0;
+    let pos = Vec2::new(random::<f32>() * 2. - 2., 2. + random::<f32>());
+    let vel = Vec2::new(random::<f32>() - 0.5, random::<f32>() - 0.5);
+    commands.spawn(ParticleBundle {
+        collider: CircleCollider { radius: 0.1 },
+        inertia: Inertia::circle(1., 0.1),
+        ..ParticleBundle::new_with_pos_and_vel(pos, vel)
+    });
+}
+
+fn despawn_fallen(mut commands: Commands, query: Query<(Entity, &Pos)>) {
+    for (entity, pos) in query.iter() {
+        if pos.0.y < -5. {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use bevy_particle_system::*;
+
+// A settled pile of marbles and a swinging chain are saved to a file and loaded
+// into a fresh world, and both worlds must then keep simulating the exact same motion
+#[test]
+fn restored_world_continues_the_same() {
+    let mut original = PhysicsWorld::new(XPBDPlugin {
+        deterministic: true,
+        ..Default::default()
+    });
+    setup(&mut original);
+    original.steps(120);
+
+    let path = std::env::temp_dir().join("bevy_particle_system_snapshot_test.ron");
+    Snapshot::capture(&mut original.app.world).save(&path).unwrap();
+
+    let mut restored = PhysicsWorld::new(XPBDPlugin {
+        deterministic: true,
+        ..Default::default()
+    });
+    let snapshot = Snapshot::load(&path).unwrap();
+    snapshot.restore(&mut restored.app.world);
+    assert_eq!(snapshot.bodies.len(), 50);
+    assert_eq!(snapshot.distance_constraints.len(), 8);
+
+    original.steps(120);
+    restored.steps(120);
+
+    let after_original = Snapshot::capture(&mut original.app.world);
+    let after_restored = Snapshot::capture(&mut restored.app.world);
+    for (a, b) in after_original.bodies.iter().zip(&after_restored.bodies) {
+        assert_eq!(a.pos.0, b.pos.0);
+        assert_eq!(a.vel.as_ref().map(|vel| vel.0), b.vel.as_ref().map(|vel| vel.0));
+    }
+}
+
+// Leaving the static bodies out of a snapshot must drop the chain link tied to the
+// anchor and renumber the other links to the chain's particles
+#[test]
+fn retained_bodies_keep_their_constraints() {
+    let mut world = PhysicsWorld::default();
+    setup(&mut world);
+    world.steps(60);
+
+    let mut snapshot = Snapshot::capture(&mut world.app.world);
+    snapshot.retain_bodies(|body| body.mass.is_some());
+    assert_eq!(snapshot.bodies.len(), 48);
+    assert_eq!(snapshot.distance_constraints.len(), 7);
+    for constraint in &snapshot.distance_constraints {
+        let (a, b) = (&snapshot.bodies[constraint.a], &snapshot.bodies[constraint.b]);
+        assert!((a.pos.0.distance(b.pos.0) - constraint.rest_length).abs() < 1e-2);
+    }
+
+    let mut restored = PhysicsWorld::default();
+    snapshot.restore(&mut restored.app.world);
+    restored.steps(60);
+}
+
+fn setup(world: &mut PhysicsWorld) {
+    world.spawn(StaticBoxBundle {
+        pos: Pos(Vec2::new(0., -3.5)),
+        collider: BoxCollider {
+            size: Vec2::new(10., 1.),
+        },
+        ..Default::default()
+    });
+    for i in 0..40 {
+        let pos = Vec2::new(-2. + (i % 8) as f32 * 0.25, -2. + (i / 8) as f32 * 0.25);
+        world.spawn(ParticleBundle {
+            collider: CircleCollider { radius: 0.1 },
+            inertia: Inertia::circle(1., 0.1),
+            ..ParticleBundle::new_with_pos_and_vel(pos, Vec2::ZERO)
+        });
+    }
+
+    // A chain hanging from a static anchor, released from the side
+    let anchor = world
+        .spawn(StaticBoxBundle {
+            pos: Pos(Vec2::new(2., 2.)),
+            collider: BoxCollider {
+                size: Vec2::splat(0.1),
+            },
+            ..Default::default()
+        })
+        .id();
+    let mut previous = anchor;
+    for i in 1..=8 {
+        let link = world
+            .spawn(ParticleBundle {
+                collider: CircleCollider { radius: 0.1 },
+                inertia: Inertia::circle(1., 0.1),
+                ..ParticleBundle::new_with_pos_and_vel(Vec2::new(2. + i as f32 * 0.3, 2.), Vec2::ZERO)
+            })
+            .id();
+        world.spawn(DistanceConstraint {
+            a: previous,
+            b: link,
+            rest_length: 0.3,
+            compliance: 0.,
+        });
+        previous = link;
+    }
+}
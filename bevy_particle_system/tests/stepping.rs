@@ -0,0 +1,85 @@
+use std::time::{Duration, Instant};
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use bevy_particle_system::*;
+
+#[test]
+#[should_panic(expected = "timestep must be positive")]
+fn zero_timestep_is_rejected() {
+    PhysicsWorld::new(XPBDPlugin {
+        timestep: 0.,
+        ..Default::default()
+    });
+}
+
+// Changing the resource later is caught on the next step instead of looping forever
+#[test]
+#[should_panic(expected = "timestep must be positive")]
+fn negative_timestep_resource_is_rejected() {
+    let mut world = PhysicsWorld::default();
+    world.step();
+    world.insert_resource(Timestep(-1.));
+    world.step();
+}
+
+#[test]
+#[should_panic(expected = "SubstepCount must be at least 1")]
+fn zero_substeps_are_rejected() {
+    let mut world = PhysicsWorld::default();
+    world.insert_resource(SubstepCount(0));
+    world.step();
+}
+
+// Steps only ever advance the simulation by the timestep, however many are queued
+#[test]
+fn steps_advance_by_timestep() {
+    let mut world = PhysicsWorld::new(XPBDPlugin {
+        timestep: 0.1,
+        ..Default::default()
+    });
+    world.insert_resource(Gravity(Vec2::ZERO));
+    let ball = world
+        .spawn(ParticleBundle::new_with_pos_and_vel(Vec2::ZERO, Vec2::X))
+        .id();
+    world.steps(5);
+    assert!((world.get::<Pos>(ball).unwrap().0.x - 0.5).abs() < 1e-5);
+}
+
+// With interpolated transforms, a body moving at 1 m/s is drawn one step behind the
+// simulation, but moves the same distance every frame when four frames make up a step,
+// instead of jumping a whole step every fourth frame
+#[test]
+fn interpolated_transform_moves_every_frame() {
+    let mut world = PhysicsWorld::new(XPBDPlugin {
+        timestep: 1. / 8.,
+        ..Default::default()
+    });
+    world
+        .insert_resource(Gravity(Vec2::ZERO))
+        .insert_resource(PhysicsStepping::RealTime)
+        .insert_resource(TransformSync::Interpolated);
+    let ball = world
+        .spawn((
+            ParticleBundle::new_with_pos_and_vel(Vec2::ZERO, Vec2::X),
+            Transform::default(),
+        ))
+        .id();
+
+    // Frames exactly 1/32 s apart, the first one has no time passed
+    let start = Instant::now();
+    let frame = |world: &mut PhysicsWorld, n: u32| {
+        let instant = start + Duration::from_secs_f64(n as f64 / 32.);
+        world.insert_resource(TimeUpdateStrategy::ManualInstant(instant));
+        world.app.update();
+    };
+    for n in 0..=4 {
+        frame(&mut world, n);
+    }
+    assert_eq!(world.get::<Pos>(ball).unwrap().0.x, 1. / 8.);
+
+    for n in 5..=16 {
+        frame(&mut world, n);
+        let drawn = world.get::<Transform>(ball).unwrap().translation.x;
+        assert!((drawn - (n - 4) as f32 / 32.).abs() < 1e-5, "{n}: {drawn}");
+    }
+}